@@ -150,6 +150,7 @@ Page {
 
 
                         appModel: appModel
+                        onNameLanguagesEdited: d.refresh()
                     }
                 }
             }
@@ -267,32 +268,15 @@ Page {
             optionalSettings.loadDefaults();
 
             appModel.urlPatterns.clear();
-            loadPermissions();
+            appModel.translations.clear();
+            appModel.permissions.clear();
+            appModel.permissions.loadDefaults(optionalSettings.target);
+            appModel.tweaks.loadDefaults();
 
             customIconSelector.source = Qt.resolvedUrl("");
             selectIconDialog.resetSelected();
         }
 
-        function loadPermissions() {
-            var perms = [
-                ["audio", i18n.tr("Play audio"), true],
-                ["content_exchange", i18n.tr("Upload files from other apps"), true],
-                ["content_exchange_source",i18n.tr("Export files to other apps"), false],
-                ["keep-display-on", i18n.tr("Keep the screen on"), false],
-                ["location", i18n.tr("Access your location"), false],
-                ["camera", i18n.tr("Access your camera"), false],
-                ["microphone", i18n.tr("Acess your microphone"), false],
-                ["sensors", i18n.tr("Access your sensors"), false]
-            ];
-
-            appModel.permissions.clear();
-
-            for (var i=0; i<perms.length; ++i) {
-                var perm = perms[i];
-                appModel.permissions.add(perm[0], perm[1], perm[2]);
-            }
-        }
-
         function refresh() {
             if (urlField.displayText !== "") {
                 screenshotDialog.url = urlField.text;
@@ -308,11 +292,35 @@ Page {
         url: urlField.text
         name: essentialSettings.name
         iconUrl: selectIconDialog.selectedIconUrl
+        maskableIcon: scraper.maskableIcons.indexOf(selectIconDialog.selectedIconUrl) !== -1
+        monochromeIconUrl: scraper.monochromeIconUrl
         themeColor: optionalSettings.themeColor
+        backgroundColor: scraper.backgroundColor
+        splashColor: optionalSettings.splashColor
+        splashHeaderColor: optionalSettings.splashHeaderColor
+        splashTitle: optionalSettings.splashTitle
+        splashShowHeader: optionalSettings.splashShowHeader
+        splashImageUrl: optionalSettings.splashShowImage ? scraper.splashIconUrl : ""
+        orientation: optionalSettings.orientation
         enableAddressBar: optionalSettings.enableAddressBar
         enableBackForward: optionalSettings.enableBackForward
         enableFullscreen: optionalSettings.enableFullscreen
         userAgent: optionalSettings.userAgent
+        target: optionalSettings.target
+        publish: optionalSettings.publish
+        maintainer: optionalSettings.maintainer
+        description: optionalSettings.description
+        namespace: optionalSettings.namespace
+        license: optionalSettings.license
+        runtime: optionalSettings.runtime
+        pullToRefresh: optionalSettings.pullToRefresh
+        storeSessionCookies: optionalSettings.storeSessionCookies
+        mediaHubAudio: optionalSettings.mediaHubAudio
+        externalLinks: optionalSettings.externalLinks
+        localContentRemoteAccess: optionalSettings.localContentRemoteAccess
+        maximized: optionalSettings.maximized
+        inspectorPort: optionalSettings.inspectorPort
+        offline: optionalSettings.offline
         useScreenshotIcon: essentialSettings.useScreenshotIcon
         useCustomIcon: essentialSettings.useCustomIcon
         customIconPath: {
@@ -324,18 +332,26 @@ Page {
         }
 
         Component.onCompleted: {
-            appModel.permissions.loadDefaults()
+            appModel.permissions.loadDefaults(optionalSettings.target)
+            appModel.tweaks.loadDefaults()
         }
 
         onCreated: {
             addDialog.close()
-            installDialog.open();
+            if (hasLintErrors) {
+                App.showHelp(i18n.tr("The shortcut is broken"),
+                             lintMessages.join("\n"),
+                             "https://docs.ubports.com/en/latest/appdev/webapp/guide.html");
+            } else {
+                installDialog.open();
+            }
         }
     }
 
     WebScraper {
         id: scraper
         url: urlField.displayText
+        languages: optionalSettings.nameLanguages
         onScraped: {
             if (siteName != "") {
                 essentialSettings.name = siteName;
@@ -345,6 +361,15 @@ Page {
             if (themeColor != "" && optionalSettings.isValidColor(themeColor)) {
                 optionalSettings.themeColor= themeColor;
             }
+            optionalSettings.scrapedOrientation = orientation;
+            if (display !== "") {
+                optionalSettings.setControls(defaultEnableFullscreen,
+                                             defaultEnableAddressBar,
+                                             defaultEnableBackForward);
+            }
+            if (backgroundColor != "" && optionalSettings.isValidColor(backgroundColor)) {
+                optionalSettings.splashColor = backgroundColor;
+            }
             if (defaultUrlPatterns !== []) {
                 appModel.urlPatterns.clear();
                 for (var i=0; i<defaultUrlPatterns.length; ++i) {
@@ -352,6 +377,18 @@ Page {
                 }
             }
 
+            appModel.translations.clear();
+            for (var i=0; i<names.length; ++i) {
+                var translation = names[i];
+                appModel.translations.add(translation[0], translation[1], translation[2]);
+            }
+
+            appModel.shortcuts.clear();
+            for (var i=0; i<shortcuts.length; ++i) {
+                var shortcut = shortcuts[i];
+                appModel.shortcuts.add(shortcut[0], shortcut[1], shortcut[2]);
+            }
+
             if (icons !== []) {
                 IconModel.clear();
                 for (var i=0; i<icons.length; ++i) {
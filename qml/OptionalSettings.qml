@@ -11,17 +11,100 @@ Item {
     property var appModel
     property bool showing: false
     property alias themeColor: colorField.text
+    property alias splashColor: splashColorField.text
+    property alias splashHeaderColor: splashHeaderColorField.text
+    property alias splashTitle: splashTitleField.text
+    property alias splashShowHeader: checkSplashHeader.checked
+    property alias splashShowImage: checkSplashImage.checked
     property alias enableAddressBar: radioTitleBar.checked
     property alias enableBackForward: radioTitleBarBackForward.checked
     property alias enableFullscreen: checkFullscreen.checked
     property string userAgent: ""
+    property alias nameLanguages: languagesField.text
+    property alias publish: checkPublish.checked
+    property alias maintainer: maintainerField.text
+    property alias namespace: namespaceField.text
+    property alias license: licenseField.text
+    property alias description: descriptionField.text
+    readonly property string runtime: checkQmlApp.checked ? "qml" : "webapp-container"
+    property alias pullToRefresh: checkPullToRefresh.checked
+    property alias storeSessionCookies: checkSessionCookies.checked
+    property alias mediaHubAudio: checkMediaHubAudio.checked
+    readonly property string externalLinks: checkExternalOverlay.checked ? "overlay" : "browser"
+    property alias localContentRemoteAccess: checkLocalContent.checked
+    property alias maximized: checkMaximized.checked
+    property alias offline: checkOffline.checked
+    readonly property int inspectorPort: checkInspector.checked
+        ? (parseInt(inspectorPortField.text) || 9221) : 0
+    property string scrapedOrientation: ""
+    readonly property string target: targetBox.model[targetBox.currentIndex].value
+    readonly property string orientation: orientationBox.currentIndex === 0
+                                          ? scrapedOrientation
+                                          : orientationBox.model[orientationBox.currentIndex].value
+    // Translations of the policy group descriptions provided by the permissions model
+    readonly property var permissionDescriptions: ({
+        "audio": i18n.tr("Play audio"),
+        "camera": i18n.tr("Access your camera"),
+        "content_exchange": i18n.tr("Upload files from other apps"),
+        "content_exchange_source": i18n.tr("Export files to other apps"),
+        "keep-display-on": i18n.tr("Keep the screen on"),
+        "location": i18n.tr("Access your location"),
+        "microphone": i18n.tr("Acess your microphone"),
+        "sensors": i18n.tr("Access your sensors"),
+        "video": i18n.tr("Play videos")
+    })
+    // Translations of the tweak descriptions provided by the tweaks model
+    readonly property var tweakDescriptions: ({
+        "hide-app-banners": i18n.tr("Hide banners asking to open the site in its app"),
+        "dark-mode": i18n.tr("Force a dark color scheme"),
+        "larger-text": i18n.tr("Enlarge the text"),
+        "no-pull-to-refresh": i18n.tr("Disable reloading the page by pulling it down")
+    })
+
+    signal nameLanguagesEdited()
+
+    onTargetChanged: {
+        if (appModel) {
+            appModel.permissions.loadDefaults(target);
+        }
+    }
 
     function loadDefaults() {
         colorField.text = "#ffffff";
+        splashColorField.text = "";
+        splashHeaderColorField.text = "";
+        splashTitleField.text = "";
+        checkSplashHeader.checked = false;
+        checkSplashImage.checked = true;
+        orientationBox.currentIndex = 0;
+        targetBox.currentIndex = 0;
         radioNoTitleBar.checked = true;
         checkFullscreen.checked = false;
         radioDefaultUA.checked = true;
         customUAField.text = "";
+        checkPublish.checked = false;
+        descriptionField.text = "";
+        checkQmlApp.checked = false;
+        checkPullToRefresh.checked = false;
+        checkSessionCookies.checked = true;
+        checkMediaHubAudio.checked = true;
+        checkExternalOverlay.checked = false;
+        checkLocalContent.checked = false;
+        checkMaximized.checked = false;
+        checkInspector.checked = false;
+        checkOffline.checked = false;
+        inspectorPortField.text = "9221";
+    }
+
+    function setControls(fullscreen, addressBar, backForward) {
+        checkFullscreen.checked = fullscreen;
+        if (backForward) {
+            radioTitleBarBackForward.checked = true;
+        } else if (addressBar) {
+            radioTitleBar.checked = true;
+        } else {
+            radioNoTitleBar.checked = true;
+        }
     }
 
     function isValidColor(color) {
@@ -52,7 +135,7 @@ Item {
             rowSpacing: Suru.units.gu(1)
 
             Label {
-                text: i18n.tr("Splash screen color (hex)")
+                text: i18n.tr("Theme color (hex)")
             }
 
             RowLayout {
@@ -76,6 +159,84 @@ Item {
                     }
                 }
             }
+
+            Label {
+                text: i18n.tr("Splash screen color (hex)")
+            }
+
+            RowLayout {
+                Layout.fillWidth: true
+
+                Rectangle {
+                    implicitHeight: Suru.units.gu(4)
+                    implicitWidth: Suru.units.gu(4)
+                    radius: Suru.units.dp(4)
+                    border.width: Suru.units.dp(1)
+                    border.color: Suru.neutralColor
+                    color: splashColorField.text !== "" ? splashColorField.text : colorField.text
+                }
+
+                LUITK.TextField {
+                    id: splashColorField
+                    Layout.fillWidth: true
+                    placeholderText: i18n.tr("Theme color")
+                    validator: RegExpValidator {
+                        regExp: /^(#(?:[0-9a-fA-F]{3}){1,2})?$/
+                    }
+                }
+            }
+
+            Label {
+                text: i18n.tr("Splash header color (hex)")
+                enabled: checkSplashHeader.checked
+            }
+
+            RowLayout {
+                Layout.fillWidth: true
+                enabled: checkSplashHeader.checked
+
+                Rectangle {
+                    implicitHeight: Suru.units.gu(4)
+                    implicitWidth: Suru.units.gu(4)
+                    radius: Suru.units.dp(4)
+                    border.width: Suru.units.dp(1)
+                    border.color: Suru.neutralColor
+                    color: splashHeaderColorField.text !== "" ? splashHeaderColorField.text
+                                                              : colorField.text
+                }
+
+                LUITK.TextField {
+                    id: splashHeaderColorField
+                    Layout.fillWidth: true
+                    placeholderText: i18n.tr("Theme color")
+                    validator: RegExpValidator {
+                        regExp: /^(#(?:[0-9a-fA-F]{3}){1,2})?$/
+                    }
+                }
+            }
+
+            Label {
+                text: i18n.tr("Splash title")
+                enabled: checkSplashHeader.checked
+            }
+
+            LUITK.TextField {
+                id: splashTitleField
+                Layout.fillWidth: true
+                enabled: checkSplashHeader.checked
+                placeholderText: i18n.tr("Name")
+            }
+        }
+
+        CheckBox {
+            id: checkSplashHeader
+            text: i18n.tr("Show header on splash screen")
+        }
+
+        CheckBox {
+            id: checkSplashImage
+            text: i18n.tr("Show icon on splash screen")
+            checked: true
         }
 
         CheckBox {
@@ -83,6 +244,47 @@ Item {
             text: i18n.tr("Fullscreen")
         }
 
+        RowLayout {
+            width: parent.width
+            spacing: Suru.units.gu(1)
+
+            Label {
+                text: i18n.tr("Orientation")
+            }
+
+            ComboBox {
+                id: orientationBox
+                Layout.fillWidth: true
+                textRole: "text"
+                model: [
+                    { text: i18n.tr("Website default"), value: "" },
+                    { text: i18n.tr("Any"), value: "any" },
+                    { text: i18n.tr("Portrait"), value: "portrait" },
+                    { text: i18n.tr("Landscape"), value: "landscape" }
+                ]
+            }
+        }
+
+        RowLayout {
+            width: parent.width
+            spacing: Suru.units.gu(1)
+
+            Label {
+                text: i18n.tr("Target system")
+            }
+
+            ComboBox {
+                id: targetBox
+                Layout.fillWidth: true
+                textRole: "text"
+                model: [
+                    { text: "Ubuntu Touch 20.04", value: "20.04" },
+                    { text: "Ubuntu Touch 16.04", value: "16.04" },
+                    { text: "Ubuntu Touch 24.04", value: "24.04" }
+                ]
+            }
+        }
+
         Label {
             text: i18n.tr("Controls")
             font.bold: true
@@ -298,6 +500,139 @@ and copy it into the text field below.")
             }
         }
 
+        RowLayout {
+            width: parent.width
+            spacing: units.gu(1)
+
+            Label {
+                text: i18n.tr("Translated names")
+                font.bold: true
+            }
+
+            Item { Layout.fillWidth: true }
+
+            Button {
+                text: i18n.tr("Add")
+                onClicked: appModel.translations.add("", "", false)
+            }
+        }
+
+        LUITK.TextField {
+            id: languagesField
+
+            property string previousText: ""
+            signal editingFinished()
+
+            width: parent.width
+            /// i18n: %1 is a placeholder for example language codes. Do not change the %1!
+            placeholderText: i18n.tr("Look up names in languages (e.g. %1)").arg("de, fr, ar")
+            inputMethodHints: Qt.ImhNoAutoUppercase | Qt.ImhNoPredictiveText
+
+            onAccepted: editingFinished()
+            onActiveFocusChanged: if (!activeFocus) editingFinished()
+            onEditingFinished: {
+                if (text !== previousText) {
+                    previousText = text;
+                    nameLanguagesEdited();
+                }
+            }
+        }
+
+        ListView {
+            width: parent.width
+            implicitHeight: contentHeight
+            interactive: false
+
+            model: appModel.translations.model
+            clip: true
+
+            delegate: Item {
+                width: parent.width
+                height: Suru.units.gu(5)
+
+                function save() {
+                    if (localeField.text === "" && nameField.text === "") {
+                        appModel.translations.remove(index);
+                    } else {
+                        appModel.translations.set(index, localeField.text, nameField.text);
+                    }
+                }
+
+                RowLayout {
+                    anchors.fill: parent
+
+                    LUITK.TextField {
+                        id: localeField
+                        Layout.preferredWidth: Suru.units.gu(10)
+
+                        text: model.locale
+                        placeholderText: "de"
+                        inputMethodHints: Qt.ImhNoAutoUppercase | Qt.ImhNoPredictiveText
+
+                        onAccepted: save()
+                        onActiveFocusChanged: if (!activeFocus && text !== model.locale) save()
+                    }
+
+                    LUITK.TextField {
+                        id: nameField
+                        Layout.fillWidth: true
+
+                        text: model.name
+                        placeholderText: i18n.tr("Name")
+                        horizontalAlignment: model.rtl ? TextInput.AlignRight : TextInput.AlignLeft
+
+                        onAccepted: save()
+                        onActiveFocusChanged: if (!activeFocus && text !== model.name) save()
+                    }
+                }
+            }
+        }
+
+        Label {
+            text: i18n.tr("Shortcuts")
+            font.bold: true
+            visible: shortcutsView.count > 0
+        }
+
+        ListView {
+            id: shortcutsView
+
+            width: parent.width
+            implicitHeight: contentHeight
+            interactive: false
+
+            model: appModel.shortcuts.model
+            clip: true
+
+            delegate: ItemDelegate {
+                width: parent.width
+                height: Suru.units.gu(5)
+
+                onClicked: {
+                    shortcutCheckbox.toggle();
+                    appModel.shortcuts.setEnabled(index, shortcutCheckbox.checked);
+                }
+
+                RowLayout {
+                    anchors.fill: parent
+
+                    CheckBox {
+                        id: shortcutCheckbox
+                        checked: model.enabled
+                        onToggled: {
+                            appModel.shortcuts.setEnabled(index, checked);
+                        }
+                    }
+
+                    Label {
+                        text: model.name
+                    }
+
+                    Item { Layout.fillWidth: true }
+                }
+            }
+        }
+
         RowLayout {
             width: parent.width
             spacing: units.gu(1)
@@ -351,12 +686,197 @@ and copy it into the text field below.")
                     }
 
                     Label {
-                        text: model.description
+                        text: permissionDescriptions[model.name] || model.description
+                    }
+
+                    Item { Layout.fillWidth: true }
+                }
+            }
+        }
+
+        Label {
+            text: i18n.tr("Tweaks")
+            font.bold: true
+        }
+
+        ListView {
+            width: parent.width
+            implicitHeight: contentHeight
+            interactive: false
+
+            model: appModel.tweaks.model
+            clip: true
+
+            delegate: ItemDelegate {
+                width: parent.width
+                height: Suru.units.gu(5)
+
+                onClicked: {
+                    tweakCheckbox.toggle();
+                    appModel.tweaks.setEnabled(index, tweakCheckbox.checked);
+                }
+
+                RowLayout {
+                    anchors.fill: parent
+
+                    CheckBox {
+                        id: tweakCheckbox
+                        checked: model.enabled
+                        onToggled: {
+                            appModel.tweaks.setEnabled(index, checked);
+                        }
+
+                        Connections {
+                            target: model
+                            onEnabledChanged: {
+                                tweakCheckbox.checked = model.enabled;
+                            }
+                        }
+                    }
+
+                    Label {
+                        text: tweakDescriptions[model.name] || model.description
                     }
 
                     Item { Layout.fillWidth: true }
                 }
             }
         }
+
+        RowLayout {
+            width: parent.width
+            spacing: units.gu(1)
+
+            Label {
+                text: i18n.tr("Publishing")
+                font.bold: true
+            }
+
+            IconButton {
+                iconName: "help"
+                onClicked: Qt.openUrlExternally("https://open-store.io/submit")
+            }
+
+            Item { Layout.fillWidth: true }
+        }
+
+        CheckBox {
+            id: checkPublish
+            text: i18n.tr("Create a package for the OpenStore")
+        }
+
+        Column {
+            width: parent.width
+            spacing: Suru.units.gu(1)
+            visible: checkPublish.checked
+
+            LUITK.TextField {
+                id: maintainerField
+                width: parent.width
+                /// i18n: Keep the format "Name <email>", only translate the words inside of it
+                placeholderText: i18n.tr("Maintainer (Name <email>)")
+                inputMethodHints: Qt.ImhNoPredictiveText
+            }
+
+            LUITK.TextField {
+                id: namespaceField
+                width: parent.width
+                placeholderText: i18n.tr("Namespace (e.g. your name in lowercase)")
+                inputMethodHints: Qt.ImhNoAutoUppercase | Qt.ImhNoPredictiveText
+                validator: RegExpValidator { regExp: /[a-z0-9][a-z0-9.+-]*/ }
+            }
+
+            LUITK.TextField {
+                id: licenseField
+                width: parent.width
+                placeholderText: i18n.tr("License (e.g. MIT)")
+                inputMethodHints: Qt.ImhNoPredictiveText
+            }
+
+            LUITK.TextField {
+                id: descriptionField
+                width: parent.width
+                placeholderText: i18n.tr("Description (default: name)")
+            }
+        }
+
+        Label {
+            text: i18n.tr("Advanced")
+            font.bold: true
+        }
+
+        CheckBox {
+            id: checkAdvanced
+            text: i18n.tr("Show advanced settings")
+        }
+
+        Column {
+            width: parent.width
+            spacing: Suru.units.gu(1)
+            visible: checkAdvanced.checked
+
+            CheckBox {
+                id: checkQmlApp
+                text: i18n.tr("Use a themed app instead of the webapp container")
+            }
+
+            CheckBox {
+                id: checkPullToRefresh
+                text: i18n.tr("Pull down to reload the page")
+                visible: checkQmlApp.checked
+            }
+
+            CheckBox {
+                id: checkSessionCookies
+                text: i18n.tr("Stay logged in after restarting the app")
+                checked: true
+            }
+
+            CheckBox {
+                id: checkMediaHubAudio
+                text: i18n.tr("Play audio in the background")
+                checked: true
+                // Ubuntu Touch 24.04 dropped media-hub
+                visible: optionalSettings.target !== "24.04" && !checkQmlApp.checked
+            }
+
+            CheckBox {
+                id: checkExternalOverlay
+                text: i18n.tr("Open links to other websites inside the app")
+            }
+
+            CheckBox {
+                id: checkOffline
+                text: i18n.tr("Make the site available offline (static sites only)")
+            }
+
+            CheckBox {
+                id: checkLocalContent
+                text: i18n.tr("Allow local content to access remote websites")
+                visible: !checkQmlApp.checked
+            }
+
+            CheckBox {
+                id: checkMaximized
+                text: i18n.tr("Open maximized on desktops")
+                visible: !checkQmlApp.checked
+            }
+
+            CheckBox {
+                id: checkInspector
+                text: i18n.tr("Enable the web inspector (for debugging only)")
+                visible: !checkQmlApp.checked
+            }
+
+            LUITK.TextField {
+                id: inspectorPortField
+                width: parent.width
+                visible: checkInspector.visible && checkInspector.checked
+                text: "9221"
+                placeholderText: i18n.tr("Inspector port")
+                inputMethodHints: Qt.ImhDigitsOnly
+                validator: IntValidator { bottom: 1; top: 65535 }
+            }
+        }
     }
 }
@@ -83,9 +83,9 @@ fn po_files() -> Vec<PathBuf> {
         .collect()
 }
 
-fn setup_qt() {
-    let qt_include_path = std::env::var("DEP_QT_INCLUDE_PATH").unwrap();
-    let qt_library_path = std::env::var("DEP_QT_LIBRARY_PATH").unwrap();
+/// Disable the app on Qt 6, which Webber doesn't support yet. The C++ code of the app lives in
+/// webber-core, which builds it.
+fn check_qt_version() {
     let qt_version = std::env::var("DEP_QT_VERSION")
         .unwrap()
         .parse::<Version>()
@@ -94,20 +94,10 @@ fn setup_qt() {
     if qt_version >= Version::new(6, 0, 0) {
         // Webber doesn't support Qt 6, yet
         println!("cargo:rustc-cfg=no_qt");
-        return;
     }
-
-    let mut config = cpp_build::Config::new();
-
-    if cfg!(target_os = "macos") {
-        config.flag("-F");
-        config.flag(&qt_library_path);
-    }
-
-    config.include(&qt_include_path).build("src/main.rs");
 }
 
 fn main() {
     gettext();
-    setup_qt();
+    check_qt_version();
 }
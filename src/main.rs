@@ -1,56 +1,45 @@
 #![feature(generic_associated_types)]
 #![recursion_limit = "256"]
 
+#[cfg(not(no_qt))]
 #[macro_use]
 extern crate cstr;
+#[cfg(not(no_qt))]
 #[macro_use]
 extern crate qmetaobject;
 
+#[cfg(not(no_qt))]
 use std::env;
+#[cfg(not(no_qt))]
 use std::path::PathBuf;
 
+#[cfg(not(no_qt))]
 use gettextrs::{bindtextdomain, textdomain};
+#[cfg(not(no_qt))]
 use qmetaobject::*;
 
-mod click;
-mod fetch_and_resolve;
-mod fetchable;
-mod icon_model;
-mod icon_provider;
-mod model;
-mod pwa;
+#[cfg(not(no_qt))]
 mod qrc;
-mod qt;
-mod resolvable;
-mod scraper;
-mod serde_utils;
 
-use crate::icon_model::IconModel;
-use crate::icon_provider::IconProvider;
-use crate::qt::image_provider::AddPixmapProvider;
+#[cfg(not(no_qt))]
+use webber_core::{
+    icon_model::IconModel, icon_provider::IconProvider, model,
+    qt::image_provider::AddPixmapProvider,
+};
 
+#[cfg(no_qt)]
+fn main() {
+    eprintln!("Webber doesn't support Qt 6, yet");
+    std::process::exit(1);
+}
+
+#[cfg(not(no_qt))]
 fn main() {
     init_gettext();
 
     std::env::set_var("QTWEBENGINE_CHROMIUM_FLAGS", "--disable-gpu --disable-viz-display-compositor --enable-features=OverlayScrollbar,OverlayScrollbarFlashAfterAnyScrollUpdate,OverlayScrollbarFlashWhenMouseEnter");
 
-    unsafe {
-        cpp! {[]{
-            QtWebEngine::initialize();
-        }}
-    }
-
-    unsafe {
-        cpp! {{
-            #include <QtCore/QCoreApplication>
-            #include <QtCore/QString>
-            #include <QtWebEngine/QtWebEngine>
-        }}
-        cpp! {[]{
-            QCoreApplication::setAttribute(Qt::AA_ShareOpenGLContexts);
-            QCoreApplication::setApplicationName(QStringLiteral("webber.timsueberkrueb"));
-        }}
-    }
+    webber_core::qt::init_application();
     QQuickStyle::set_style("Suru");
     qrc::load();
     qml_register_type::<model::WebScraper>(cstr!("Webber"), 1, 0, cstr!("WebScraper"));
@@ -59,6 +48,12 @@ fn main() {
     qml_register_type::<model::UrlPatterns>(cstr!("Webber"), 1, 0, cstr!("UrlPatterns"));
     qml_register_type::<model::PermissionsModel>(cstr!("Webber"), 1, 0, cstr!("PermissionsModel"));
     qml_register_type::<model::Permissions>(cstr!("Webber"), 1, 0, cstr!("Permissions"));
+    qml_register_type::<model::TweaksModel>(cstr!("Webber"), 1, 0, cstr!("TweaksModel"));
+    qml_register_type::<model::Tweaks>(cstr!("Webber"), 1, 0, cstr!("Tweaks"));
+    qml_register_type::<model::ShortcutsModel>(cstr!("Webber"), 1, 0, cstr!("ShortcutsModel"));
+    qml_register_type::<model::Shortcuts>(cstr!("Webber"), 1, 0, cstr!("Shortcuts"));
+    qml_register_type::<model::TranslationsModel>(cstr!("Webber"), 1, 0, cstr!("TranslationsModel"));
+    qml_register_type::<model::Translations>(cstr!("Webber"), 1, 0, cstr!("Translations"));
 
     let mut engine = QmlEngine::new();
 
@@ -94,6 +89,7 @@ fn main() {
     engine.exec();
 }
 
+#[cfg(not(no_qt))]
 fn init_gettext() {
     let domain = "webber.timsueberkrueb";
     textdomain(domain).expect("Failed to setup gettext domain");
@@ -1,13 +0,0 @@
-use url::Url;
-
-use reqwest::blocking as reqwest;
-
-pub(super) struct ManifestContent(pub String);
-
-pub(super) fn download_manifest(url: &Url) -> Result<ManifestContent, String> {
-    reqwest::get(url.as_ref())
-        .map_err(|err| err.to_string())?
-        .text()
-        .map_err(|err| err.to_string())
-        .map(ManifestContent)
-}
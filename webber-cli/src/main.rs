@@ -0,0 +1,713 @@
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use csscolorparser::Color;
+
+use webber_core::click;
+use webber_core::pwa;
+use webber_core::scraper;
+
+const USAGE: &str = "Usage: webber-cli build <url> [options]
+       webber-cli bundle <title> <url>... [--target <release>]
+                                          [--package-version <version>]
+                                          [--reproducible] [publishing options]
+       webber-cli inspect <click> [--extract-icon <dir>]
+       webber-cli lint <click>
+
+build: Scrape <url>, create a click package (or desktop entry) for it and print its path.
+
+bundle: Scrape every <url> and create a single click package titled <title>, installing a
+        shortcut with the scraped settings for each of them. Prints the package path.
+
+inspect: Print the settings of a click package created by Webber.
+
+lint: Check a click package for problems. Exits with status 1 if errors were found.
+
+Options:
+    --name <name>              Name of the shortcut (default: scraped site name or title)
+    --lang <language>          Look up the name of the site in <language>, e.g. de or pt-BR
+                               (can be repeated)
+    --localized-name <language>=<name>
+                               Name of the shortcut in <language> (can be repeated,
+                               default: names looked up with --lang and the name in the
+                               site's own language)
+    --icon <path or url>       Icon of the shortcut (default: preferred scraped icon)
+    --maskable                 The icon is maskable and gets cropped to its safe zone
+                               (default: whether the scraped icon is maskable)
+    --monochrome-icon <path or url>
+                               Monochrome icon, tinted with the theme color and used as
+                               symbolic icon (default: scraped monochrome icon)
+    --theme-color <color>      Theme color (default: scraped theme color)
+    --background-color <color> Background of maskable icons (default: scraped background
+                               color or theme color)
+    --splash-color <color>     Splash screen color (default: background color)
+    --splash-image <path or url>
+                               Image shown on the splash screen (default: largest scraped
+                               icon, if large enough)
+    --no-splash-image          Don't show an image on the splash screen
+    --splash-header            Show a header on the splash screen
+    --splash-header-color <color>
+                               Color of the splash screen header (default: theme color)
+    --splash-title <title>     Title of the splash screen header (default: name)
+    --orientation <orientation>
+                               Lock the orientation, using the keywords of web app
+                               manifests (e.g. landscape, default: scraped orientation)
+    --shortcut <name>          Include the scraped shortcut <name> as action (can be
+                               repeated, default: all scraped shortcuts)
+    --no-shortcuts             Don't include any scraped shortcuts
+    --permission <policy>      AppArmor policy group to add (can be repeated)
+    --template <template>      AppArmor template: ubuntu-webapp or ubuntu-sdk
+                               (default: ubuntu-webapp)
+    --read-path <path>         Grant read access to <path> (can be repeated)
+    --write-path <path>        Grant write access to <path> (can be repeated)
+    --pattern <pattern>        Url pattern (can be repeated, default: scraped url patterns)
+    --tweak <tweak>            Apply a built-in tweak (can be repeated): hide-app-banners,
+                               dark-mode, larger-text or no-pull-to-refresh
+    --user-script <path>       Inject a .js or .css file into every page (can be repeated)
+    --offline                  Ship the static files of the site in the package and open
+                               them instead of the site, for static single-page apps
+    --offline-budget <MB>      Size budget of the offline files (default: 20, implies
+                               --offline)
+    --user-agent <ua>          Custom User-Agent header
+    --display <mode>           Pick the controls for a manifest display mode: fullscreen,
+                               standalone, minimal-ui or browser (default: scraped mode)
+    --address-bar              Show a title bar
+    --back-forward             Show a title bar with back/forward buttons
+    --fullscreen               Launch in fullscreen mode
+                               (--address-bar, --back-forward and --fullscreen replace the
+                               controls picked by the display mode)
+    --target <release>         Ubuntu Touch release to build for: 16.04, 20.04 or 24.04
+                               (default: 20.04)
+    --package-version <version>
+                               Package version (default: successor of the previous build,
+                               builds with an explicit version aren't recorded)
+    --upgrade <click>          Build an upgrade of the given, previously built click package
+    --launcher <browser>       Install a desktop entry launching chromium, epiphany or firefox
+                               instead of creating a click package
+    --reproducible             Create a byte-identical package for identical inputs, using
+                               SOURCE_DATE_EPOCH (default: 0) as timestamp
+    --extract-icon <dir>       Write the icon of the inspected package to <dir>, the icons
+                               of bundled apps to <dir>/<app>
+    -h, --help                 Print this help message
+
+Container options (advanced settings of the webapp container):
+    --runtime <runtime>        Run the app in the webapp-container or in a qml app with a
+                               themed header (default: webapp-container)
+    --pull-to-refresh          Reload the page when pulling it down (qml runtime only)
+    --no-session-cookies       Don't keep session cookies across restarts
+    --no-media-hub-audio       Don't play audio through media-hub
+    --external-links <mode>    Open links leaving the url patterns in the browser or in an
+                               overlay (default: browser)
+    --local-content-remote-access
+                               Allow local content to load remote urls
+    --maximized                Open the app maximized on desktops
+    --inspector <port>         Run the web inspector on <port>, for debugging only
+
+Publishing options (build a store package instead of an anonymous shortcut):
+    --maintainer <maintainer>  Maintainer in the format \"Name <email>\"
+    --namespace <namespace>    Suffix of the package name <appname>.<namespace>, usually
+                               the developer or organization name
+    --license <license>        License of the package, e.g. MIT
+    --description <text>       Description of the package (default: name)
+                               (--maintainer, --namespace and --license are required if any
+                               publishing option is given)";
+
+#[derive(Default)]
+struct BuildArgs {
+    url: String,
+    name: Option<String>,
+    languages: Vec<String>,
+    localized_names: Vec<(String, String)>,
+    icon: Option<String>,
+    maskable_icon: bool,
+    monochrome_icon: Option<String>,
+    theme_color: Option<String>,
+    background_color: Option<String>,
+    splash_color: Option<String>,
+    splash_image: Option<String>,
+    no_splash_image: bool,
+    splash_header: bool,
+    splash_header_color: Option<String>,
+    splash_title: Option<String>,
+    orientation: Option<pwa::Orientation>,
+    shortcuts: Vec<String>,
+    no_shortcuts: bool,
+    permissions: Vec<String>,
+    policy_template: click::PolicyTemplate,
+    read_paths: Vec<String>,
+    write_paths: Vec<String>,
+    url_patterns: Vec<String>,
+    user_scripts: Vec<click::UserScript>,
+    offline_snapshot: Option<click::OfflineSnapshot>,
+    user_agent: Option<String>,
+    target: click::Target,
+    display: Option<pwa::DisplayMode>,
+    enable_address_bar: bool,
+    enable_back_forward: bool,
+    enable_fullscreen: bool,
+    container: click::ContainerOptions,
+    version: Option<String>,
+    upgrade: Option<PathBuf>,
+    launcher: Option<click::Launcher>,
+    reproducible: bool,
+    publish: PublishArgs,
+}
+
+impl BuildArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut res = BuildArgs::default();
+        let mut url = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", flag))
+            };
+            match arg.as_str() {
+                "--name" => res.name = Some(value(&arg)?),
+                "--lang" => res.languages.push(value(&arg)?),
+                "--localized-name" => {
+                    let value = value(&arg)?;
+                    let (lang, name) = value
+                        .split_once('=')
+                        .ok_or_else(|| format!("Expected <language>=<name>: {}", value))?;
+                    res.localized_names.push((lang.to_owned(), name.to_owned()));
+                }
+                "--icon" => res.icon = Some(value(&arg)?),
+                "--maskable" => res.maskable_icon = true,
+                "--monochrome-icon" => res.monochrome_icon = Some(value(&arg)?),
+                "--theme-color" => res.theme_color = Some(value(&arg)?),
+                "--background-color" => res.background_color = Some(value(&arg)?),
+                "--splash-color" => res.splash_color = Some(value(&arg)?),
+                "--splash-image" => res.splash_image = Some(value(&arg)?),
+                "--no-splash-image" => res.no_splash_image = true,
+                "--splash-header" => res.splash_header = true,
+                "--splash-header-color" => res.splash_header_color = Some(value(&arg)?),
+                "--splash-title" => res.splash_title = Some(value(&arg)?),
+                "--orientation" => res.orientation = Some(value(&arg)?.parse()?),
+                "--shortcut" => res.shortcuts.push(value(&arg)?),
+                "--no-shortcuts" => res.no_shortcuts = true,
+                "--permission" => res.permissions.push(value(&arg)?),
+                "--template" => res.policy_template = value(&arg)?.parse()?,
+                "--read-path" => res.read_paths.push(value(&arg)?),
+                "--write-path" => res.write_paths.push(value(&arg)?),
+                "--pattern" => res.url_patterns.push(value(&arg)?),
+                "--tweak" => {
+                    let id = value(&arg)?;
+                    let tweak =
+                        click::tweak(&id).ok_or_else(|| format!("Unknown tweak: {}", id))?;
+                    res.user_scripts.push(tweak.user_script());
+                }
+                "--user-script" => {
+                    let path = PathBuf::from(value(&arg)?);
+                    let script = click::UserScript::from_file(&path)
+                        .map_err(|err| format!("Invalid user script: {}", err))?;
+                    res.user_scripts.push(script);
+                }
+                "--offline" => {
+                    res.offline_snapshot.get_or_insert_with(Default::default);
+                }
+                "--offline-budget" => {
+                    let budget = value(&arg)?;
+                    let megabytes: u64 = budget
+                        .parse()
+                        .ok()
+                        .filter(|&megabytes| megabytes > 0)
+                        .ok_or_else(|| format!("Invalid offline budget: {}", budget))?;
+                    res.offline_snapshot = Some(click::OfflineSnapshot {
+                        max_size: megabytes * 1024 * 1024,
+                    });
+                }
+                "--user-agent" => res.user_agent = Some(value(&arg)?),
+                "--target" => res.target = value(&arg)?.parse()?,
+                "--display" => res.display = Some(value(&arg)?.parse()?),
+                "--address-bar" => res.enable_address_bar = true,
+                "--back-forward" => res.enable_back_forward = true,
+                "--fullscreen" => res.enable_fullscreen = true,
+                "--runtime" => res.container.runtime = value(&arg)?.parse()?,
+                "--pull-to-refresh" => res.container.pull_to_refresh = true,
+                "--no-session-cookies" => res.container.store_session_cookies = false,
+                "--no-media-hub-audio" => res.container.media_hub_audio = false,
+                "--external-links" => res.container.external_links = value(&arg)?.parse()?,
+                "--local-content-remote-access" => res.container.local_content_remote_access = true,
+                "--maximized" => res.container.maximized = true,
+                "--inspector" => {
+                    let port = value(&arg)?;
+                    let port = port
+                        .parse()
+                        .map_err(|_| format!("Invalid inspector port: {}", port))?;
+                    res.container.inspector_port = Some(port);
+                }
+                "--package-version" => res.version = Some(value(&arg)?),
+                "--upgrade" => res.upgrade = Some(PathBuf::from(value(&arg)?)),
+                "--launcher" => res.launcher = Some(value(&arg)?.parse()?),
+                "--reproducible" => res.reproducible = true,
+                _ if res.publish.parse(&arg, &mut value)? => {}
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if url.is_none() => url = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        res.url = url.ok_or_else(|| "Missing url".to_owned())?;
+        Ok(res)
+    }
+}
+
+struct BundleArgs {
+    name: String,
+    urls: Vec<String>,
+    target: click::Target,
+    version: Option<String>,
+    reproducible: bool,
+    publish: PublishArgs,
+}
+
+impl BundleArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut name = None;
+        let mut urls = Vec::new();
+        let mut target = click::Target::default();
+        let mut version = None;
+        let mut reproducible = false;
+        let mut publish = PublishArgs::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", flag))
+            };
+            match arg.as_str() {
+                "--target" => target = value(&arg)?.parse()?,
+                "--package-version" => version = Some(value(&arg)?),
+                "--reproducible" => reproducible = true,
+                _ if publish.parse(&arg, &mut value)? => {}
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if name.is_none() => name = Some(arg),
+                _ => urls.push(arg),
+            }
+        }
+
+        if urls.is_empty() {
+            return Err("Missing url".to_owned());
+        }
+        Ok(Self {
+            name: name.ok_or_else(|| "Missing bundle title".to_owned())?,
+            urls,
+            target,
+            version,
+            reproducible,
+            publish,
+        })
+    }
+}
+
+/// The publishing options shared by `build` and `bundle`
+#[derive(Default)]
+struct PublishArgs {
+    maintainer: Option<String>,
+    description: Option<String>,
+    namespace: Option<String>,
+    license: Option<String>,
+}
+
+impl PublishArgs {
+    /// Take the value of `arg` if it is a publishing option, returns whether it was one
+    fn parse(
+        &mut self,
+        arg: &str,
+        value: &mut impl FnMut(&str) -> Result<String, String>,
+    ) -> Result<bool, String> {
+        let field = match arg {
+            "--maintainer" => &mut self.maintainer,
+            "--description" => &mut self.description,
+            "--namespace" => &mut self.namespace,
+            "--license" => &mut self.license,
+            _ => return Ok(false),
+        };
+        *field = Some(value(arg)?);
+        Ok(true)
+    }
+
+    /// The publishing metadata of a package titled `name`, `None` if no option was given
+    fn publishing(self, name: &str) -> Result<Option<click::Publishing>, String> {
+        if self.maintainer.is_none()
+            && self.description.is_none()
+            && self.namespace.is_none()
+            && self.license.is_none()
+        {
+            return Ok(None);
+        }
+        let required = |value: Option<String>, flag: &str| {
+            value.ok_or_else(|| format!("Publishing a package requires {}", flag))
+        };
+        Ok(Some(click::Publishing {
+            maintainer: required(self.maintainer, "--maintainer")?,
+            description: self.description.unwrap_or_else(|| name.to_owned()),
+            namespace: required(self.namespace, "--namespace")?,
+            license: required(self.license, "--license")?,
+        }))
+    }
+}
+
+struct InspectArgs {
+    path: PathBuf,
+    extract_icon: Option<PathBuf>,
+}
+
+impl InspectArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut path = None;
+        let mut extract_icon = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--extract-icon" => {
+                    let dir = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for {}", arg))?;
+                    extract_icon = Some(PathBuf::from(dir));
+                }
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if path.is_none() => path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        Ok(Self {
+            path: path.ok_or_else(|| "Missing click package path".to_owned())?,
+            extract_icon,
+        })
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+
+    let res = match args.next().as_deref() {
+        Some("build") => BuildArgs::parse(args).map(build),
+        Some("bundle") => BundleArgs::parse(args).map(bundle),
+        Some("inspect") => InspectArgs::parse(args).map(inspect),
+        Some("lint") => match (args.next(), args.next()) {
+            (Some(path), None) => Ok(lint(Path::new(&path))),
+            (None, _) => Err("Missing click package path".to_owned()),
+            (Some(_), Some(arg)) => Err(format!("Unexpected argument: {}", arg)),
+        },
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            return;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    match res {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn build(args: BuildArgs) -> Result<(), Box<dyn Error>> {
+    let launcher = args.launcher;
+    let (version, upgrade) = (args.version.clone(), args.upgrade.clone());
+    let mut package = scrape_package(args)?;
+
+    if let Some(launcher) = launcher {
+        let (path, warnings) = package.install_desktop_entry(launcher)?;
+        for msg in warnings {
+            eprintln!("{}", msg);
+        }
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    // The history is only involved in builds deriving their version
+    let history = match version {
+        Some(version) => {
+            package.version = version;
+            None
+        }
+        None => {
+            let history = click::BuildHistory::new()?;
+            match upgrade {
+                Some(previous) => package.upgrade_from(&previous)?,
+                None => package.version = history.next_version(&package)?,
+            }
+            Some(history)
+        }
+    };
+
+    let (path, warnings) = package.create()?;
+    if let Some(history) = history {
+        history.record(&package, &path)?;
+    }
+
+    for msg in warnings.into_iter().chain(click::lint(&path)?) {
+        eprintln!("{}", msg);
+    }
+
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn bundle(args: BundleArgs) -> Result<(), Box<dyn Error>> {
+    let target = args.target;
+    let publishing = args.publish.publishing(args.name.trim())?;
+    let packages = args
+        .urls
+        .into_iter()
+        .map(|url| {
+            scrape_package(BuildArgs {
+                url,
+                target,
+                ..BuildArgs::default()
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut bundle = click::Bundle {
+        name: args.name,
+        packages,
+        version: click::INITIAL_VERSION.to_owned(),
+        reproducible: args.reproducible,
+        publishing,
+    };
+    bundle.sanitize();
+
+    let history = match args.version {
+        Some(version) => {
+            bundle.version = version;
+            None
+        }
+        None => {
+            let history = click::BuildHistory::new()?;
+            bundle.version = history.next_bundle_version(&bundle)?;
+            Some(history)
+        }
+    };
+
+    let (path, warnings) = bundle.create()?;
+    if let Some(history) = history {
+        history.record_bundle(&bundle, &path)?;
+    }
+
+    for msg in warnings.into_iter().chain(click::lint(&path)?) {
+        eprintln!("{}", msg);
+    }
+
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Scrape the site of `args` and create the package described by `args`, falling back to the
+/// scraped settings
+fn scrape_package(args: BuildArgs) -> Result<click::Package, Box<dyn Error>> {
+    let url = scraper::validate_url(args.url).map_err(|s| format!("Invalid url: {}", s))?;
+    let site = scraper::scrape(&url).map_err(|err| format!("Failed to load site: {}", err))?;
+
+    let mut names = Vec::<click::LocalizedName>::new();
+    for (lang, name, rtl) in scraper::scrape_names(&url, &site, &args.languages) {
+        // Names with language tags that aren't valid locales are skipped
+        if let Ok(name) = click::LocalizedName::new(&lang, name, rtl) {
+            if !names.iter().any(|other| other.locale == name.locale) {
+                names.push(name);
+            }
+        }
+    }
+    // A custom name replaces the scraped name in the site's own language as well
+    if args.name.is_some() {
+        names.retain(|name| Some(&name.name) != site.name());
+    }
+    for (lang, name) in args.localized_names {
+        let name = click::LocalizedName::new(&lang, name, None)?;
+        names.retain(|other| other.locale != name.locale);
+        names.push(name);
+    }
+
+    let name = args
+        .name
+        .or(site.site_name)
+        .or(site.title)
+        .filter(|name| !name.trim().is_empty())
+        .ok_or("Failed to determine a name, please pass --name")?;
+    let publishing = args.publish.publishing(name.trim())?;
+
+    let (icon, maskable_icon) = match args.icon {
+        Some(icon) => (parse_icon(icon), args.maskable_icon),
+        None => match site.icons.first() {
+            Some(icon_url) => (
+                click::Icon::Remote(icon_url.to_string()),
+                args.maskable_icon || site.maskable_icons.contains(icon_url),
+            ),
+            None => (click::Icon::Local(String::new()), false),
+        },
+    };
+
+    let monochrome_icon = match args.monochrome_icon {
+        Some(icon) => Some(parse_icon(icon)),
+        None => site
+            .monochrome_icons
+            .first()
+            .map(|icon_url| click::Icon::Remote(icon_url.to_string())),
+    };
+
+    let theme_color = match args.theme_color {
+        Some(color) => color
+            .parse::<Color>()
+            .map_err(|err| format!("Invalid theme color: {}", err))?,
+        None => site
+            .theme_color
+            .unwrap_or_else(|| Color::from_rgb_u8(255, 255, 255)),
+    };
+
+    let background_color = match args.background_color {
+        Some(color) => color
+            .parse::<Color>()
+            .map_err(|err| format!("Invalid background color: {}", err))?
+            .to_hex_string(),
+        None => site
+            .background_color
+            .map(|color| color.to_hex_string())
+            .unwrap_or_default(),
+    };
+
+    let splash = click::Splash {
+        color: parse_color(args.splash_color, "splash color")?,
+        header_color: parse_color(args.splash_header_color, "splash header color")?,
+        image: match args.splash_image {
+            _ if args.no_splash_image => None,
+            Some(image) => Some(parse_icon(image)),
+            None => site
+                .splash_icon
+                .map(|icon_url| click::Icon::Remote(icon_url.to_string())),
+        },
+        title: args.splash_title.unwrap_or_default(),
+        show_header: args.splash_header,
+    };
+
+    let flags = if args.enable_address_bar || args.enable_back_forward || args.enable_fullscreen {
+        click::DisplayFlags {
+            enable_address_bar: args.enable_address_bar,
+            enable_back_forward: args.enable_back_forward,
+            enable_fullscreen: args.enable_fullscreen,
+        }
+    } else {
+        args.display
+            .or(site.display)
+            .map(click::DisplayFlags::from)
+            .unwrap_or_default()
+    };
+
+    let (no_shortcuts, shortcuts) = (args.no_shortcuts, &args.shortcuts);
+    let actions = site
+        .shortcuts
+        .into_iter()
+        .filter(|shortcut| {
+            !no_shortcuts && (shortcuts.is_empty() || shortcuts.contains(&shortcut.name))
+        })
+        .map(click::Action::from)
+        .collect();
+
+    let url_patterns = if args.url_patterns.is_empty() {
+        site.default_url_patterns
+    } else {
+        args.url_patterns
+    };
+
+    let mut package = click::Package {
+        url: url.to_string(),
+        name,
+        names,
+        theme_color: theme_color.to_hex_string(),
+        background_color,
+        icon,
+        maskable_icon,
+        monochrome_icon,
+        splash,
+        orientation: args.orientation.or(site.orientation),
+        actions,
+        url_patterns: url_patterns.join(","),
+        permissions: args.permissions,
+        policy_template: args.policy_template,
+        read_paths: args.read_paths,
+        write_paths: args.write_paths,
+        enable_address_bar: flags.enable_address_bar,
+        enable_back_forward: flags.enable_back_forward,
+        enable_fullscreen: flags.enable_fullscreen,
+        user_agent: args.user_agent.unwrap_or_default(),
+        container: args.container,
+        user_scripts: args.user_scripts,
+        offline_snapshot: args.offline_snapshot,
+        target: args.target,
+        version: click::INITIAL_VERSION.to_owned(),
+        reproducible: args.reproducible,
+        publishing,
+    };
+    package.sanitize();
+    Ok(package)
+}
+
+/// Validate an optional color and return it as hex string, or an empty string if not given
+fn parse_color(color: Option<String>, what: &str) -> Result<String, String> {
+    match color {
+        Some(color) => color
+            .parse::<Color>()
+            .map(|color| color.to_hex_string())
+            .map_err(|err| format!("Invalid {}: {}", what, err)),
+        None => Ok(String::new()),
+    }
+}
+
+fn parse_icon(icon: String) -> click::Icon {
+    if icon.starts_with("http://") || icon.starts_with("https://") {
+        click::Icon::Remote(icon)
+    } else {
+        click::Icon::Local(icon)
+    }
+}
+
+fn inspect(args: InspectArgs) -> Result<(), Box<dyn Error>> {
+    let mut apps = click::read_bundle(&args.path)?;
+    let bundled = apps.len() > 1;
+
+    for contents in &mut apps {
+        if let Some(ref dir) = args.extract_icon {
+            // Keep the icons of bundled apps apart
+            if bundled {
+                contents.extract_icon(&dir.join(&contents.appname))?;
+            } else {
+                contents.extract_icon(dir)?;
+            }
+        }
+
+        println!("{:#?}", contents.package);
+    }
+    Ok(())
+}
+
+fn lint(path: &Path) -> Result<(), Box<dyn Error>> {
+    let messages = click::lint(path)?;
+
+    for msg in &messages {
+        println!("{}", msg);
+    }
+
+    if messages
+        .iter()
+        .any(|msg| msg.severity == click::Severity::Error)
+    {
+        return Err("The package contains errors".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+/* Copyright (C) 2018 Olivier Goffart <ogoffart@woboq.com>
+Permission is hereby granted, free of charge, to any person obtaining a copy of this software and
+associated documentation files (the "Software"), to deal in the Software without restriction,
+including without limitation the rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software is furnished to do so,
+subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all copies or substantial
+portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT
+NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES
+OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+#[cfg(feature = "qt")]
+use semver::Version;
+
+#[cfg(feature = "qt")]
+fn setup_qt() {
+    let qt_include_path = std::env::var("DEP_QT_INCLUDE_PATH").unwrap();
+    let qt_library_path = std::env::var("DEP_QT_LIBRARY_PATH").unwrap();
+    let qt_version = std::env::var("DEP_QT_VERSION")
+        .unwrap()
+        .parse::<Version>()
+        .expect("Parsing Qt version failed");
+
+    if qt_version >= Version::new(6, 0, 0) {
+        // Webber doesn't support Qt 6, yet
+        println!("cargo:rustc-cfg=no_qt");
+        return;
+    }
+
+    let mut config = cpp_build::Config::new();
+
+    if cfg!(target_os = "macos") {
+        config.flag("-F");
+        config.flag(&qt_library_path);
+    }
+
+    config.include(&qt_include_path).build("src/lib.rs");
+}
+
+fn main() {
+    #[cfg(feature = "qt")]
+    setup_qt();
+}
@@ -0,0 +1,381 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::Target;
+
+/// A policy group, i.e. a set of AppArmor rules an app can request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyGroup {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether the webapp container makes use of the group. Only those groups are offered for
+    /// shortcuts.
+    pub webapp: bool,
+    /// Whether new shortcuts request the group by default
+    pub default: bool,
+    /// The last policy version providing the group, `None` if it's still available
+    pub until: Option<&'static str>,
+}
+
+impl PolicyGroup {
+    const fn new(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            webapp: false,
+            default: false,
+            until: None,
+        }
+    }
+
+    const fn webapp(self, default: bool) -> Self {
+        Self {
+            webapp: true,
+            default,
+            ..self
+        }
+    }
+
+    const fn until(self, policy_version: &'static str) -> Self {
+        Self {
+            until: Some(policy_version),
+            ..self
+        }
+    }
+
+    /// Whether the group is available with the known policy version `policy_version`
+    fn available_in(&self, policy_version: &str) -> bool {
+        self.until.map_or(true, |until| policy_version <= until)
+    }
+}
+
+/// The known policy versions, oldest first
+const POLICY_VERSIONS: &[&str] = &["16.04", "20.04"];
+
+/// The policy groups of all known policy versions, see
+/// https://docs.ubports.com/en/latest/appdev/platform/apparmor.html
+const POLICY_GROUPS: &[PolicyGroup] = &[
+    PolicyGroup::new("accounts", "Use your online accounts"),
+    PolicyGroup::new("audio", "Play audio").webapp(true),
+    PolicyGroup::new("calendar", "Access your calendar"),
+    PolicyGroup::new("camera", "Access your camera").webapp(false),
+    PolicyGroup::new("connectivity", "Check the network connectivity"),
+    PolicyGroup::new("contacts", "Access your contacts"),
+    PolicyGroup::new("content_exchange", "Upload files from other apps").webapp(true),
+    PolicyGroup::new("content_exchange_source", "Export files to other apps").webapp(false),
+    PolicyGroup::new("debug", "Allow debugging"),
+    PolicyGroup::new("document_files", "Read and write your documents"),
+    PolicyGroup::new("document_files_read", "Read your documents"),
+    // Dropped in 20.04 along with the Friends service
+    PolicyGroup::new("friends", "Use your social network accounts").until("16.04"),
+    PolicyGroup::new("history", "Access your history"),
+    PolicyGroup::new("in-app-purchases", "Make in-app purchases"),
+    PolicyGroup::new("keep-display-on", "Keep the screen on").webapp(false),
+    PolicyGroup::new("location", "Access your location").webapp(false),
+    PolicyGroup::new("microphone", "Access your microphone").webapp(false),
+    PolicyGroup::new("music_files", "Read and write your music"),
+    PolicyGroup::new("music_files_read", "Read your music"),
+    PolicyGroup::new("networking", "Access the network"),
+    PolicyGroup::new("picture_files", "Read and write your pictures"),
+    PolicyGroup::new("picture_files_read", "Read your pictures"),
+    PolicyGroup::new("push-notification-client", "Receive push notifications"),
+    PolicyGroup::new("sensors", "Access your sensors").webapp(false),
+    PolicyGroup::new("usermetrics", "Show metrics on the lock screen"),
+    PolicyGroup::new("video", "Play videos").webapp(false),
+    PolicyGroup::new("video_files", "Read and write your videos"),
+    PolicyGroup::new("video_files_read", "Read your videos"),
+    PolicyGroup::new("webview", "Display web content"),
+];
+
+/// Groups every shortcut needs, they are always part of the policy
+const REQUIRED_GROUPS: &[&str] = &["networking", "webview"];
+
+/// Pairs of groups where the first grants all access of the second
+const SUPERSEDING_GROUPS: &[(&str, &str)] = &[
+    ("document_files", "document_files_read"),
+    ("music_files", "music_files_read"),
+    ("picture_files", "picture_files_read"),
+    ("video_files", "video_files_read"),
+];
+
+/// The policy groups available with `policy_version`, `None` for unknown policy versions
+pub fn policy_groups(policy_version: &str) -> Option<Vec<PolicyGroup>> {
+    if !POLICY_VERSIONS.contains(&policy_version) {
+        return None;
+    }
+    let groups = POLICY_GROUPS
+        .iter()
+        .filter(|group| group.available_in(policy_version))
+        .copied()
+        .collect();
+    Some(groups)
+}
+
+/// The first pair of `groups` where the first group already grants the access of the second
+pub(super) fn superseded_group(groups: &[String]) -> Option<(&'static str, &'static str)> {
+    SUPERSEDING_GROUPS
+        .iter()
+        .copied()
+        .find(|(group, superseded)| {
+            groups.iter().any(|other| other == group)
+                && groups.iter().any(|other| other == superseded)
+        })
+}
+
+/// Paths in `read_path` and `write_path` have to be absolute, optionally starting with an
+/// AppArmor variable like `@{HOME}`
+pub(super) fn is_valid_policy_path(path: &str) -> bool {
+    (path.starts_with('/') || path.starts_with("@{"))
+        && !path.chars().any(|c| c.is_control() || c == '"')
+}
+
+/// The AppArmor template the app is confined with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyTemplate {
+    /// The template of the webapp container
+    #[default]
+    UbuntuWebapp,
+    /// The template of regular QML apps
+    UbuntuSdk,
+}
+
+impl PolicyTemplate {
+    pub const ALL: &'static [PolicyTemplate] =
+        &[PolicyTemplate::UbuntuWebapp, PolicyTemplate::UbuntuSdk];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PolicyTemplate::UbuntuWebapp => "ubuntu-webapp",
+            PolicyTemplate::UbuntuSdk => "ubuntu-sdk",
+        }
+    }
+}
+
+impl FromStr for PolicyTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        PolicyTemplate::ALL
+            .iter()
+            .copied()
+            .find(|template| template.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown AppArmor template \"{}\", expected ubuntu-webapp or ubuntu-sdk",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for PolicyTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The AppArmor policy of an app, as written to its `.apparmor` file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AppArmor {
+    pub template: String,
+    pub policy_groups: Vec<String>,
+    pub policy_version: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_path: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write_path: Vec<String>,
+}
+
+impl AppArmor {
+    pub(super) fn to_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    UnknownGroup {
+        group: String,
+        policy_version: String,
+    },
+    /// The first group already grants the access of the second
+    SupersededGroup(String, String),
+    InvalidPath(String),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::UnknownGroup {
+                group,
+                policy_version,
+            } => write!(
+                f,
+                "Unknown policy group \"{}\" for policy version {}",
+                group, policy_version
+            ),
+            PolicyError::SupersededGroup(group, superseded) => write!(
+                f,
+                "Policy group \"{}\" already grants the access of \"{}\"",
+                group, superseded
+            ),
+            PolicyError::InvalidPath(path) => write!(
+                f,
+                "Invalid policy path \"{}\", paths have to be absolute or start with an \
+                 AppArmor variable",
+                path
+            ),
+        }
+    }
+}
+
+impl Error for PolicyError {}
+
+/// Builds the AppArmor policy of a shortcut, rejecting groups that are unknown for the policy
+/// version or conflict with each other
+#[derive(Debug, Clone)]
+pub struct PolicyBuilder {
+    template: PolicyTemplate,
+    policy_version: &'static str,
+    policy_groups: Vec<String>,
+    read_paths: Vec<String>,
+    write_paths: Vec<String>,
+}
+
+impl PolicyBuilder {
+    /// A policy for `target`, granting the network and web view access every shortcut needs
+    pub fn new(target: Target) -> Self {
+        Self {
+            template: PolicyTemplate::default(),
+            policy_version: target.policy_version(),
+            policy_groups: REQUIRED_GROUPS
+                .iter()
+                .map(|&group| group.to_owned())
+                .collect(),
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+        }
+    }
+
+    pub fn template(mut self, template: PolicyTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Request the policy group `group`. Groups requested twice are only included once.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        let group = group.into();
+        if !self.policy_groups.contains(&group) {
+            self.policy_groups.push(group);
+        }
+        self
+    }
+
+    pub fn groups<S: Into<String>>(self, groups: impl IntoIterator<Item = S>) -> Self {
+        groups
+            .into_iter()
+            .fold(self, |builder, group| builder.group(group))
+    }
+
+    /// Grant read access to `path` in addition to the access of the policy groups
+    pub fn read_path(mut self, path: impl Into<String>) -> Self {
+        self.read_paths.push(path.into());
+        self
+    }
+
+    /// Grant write access to `path` in addition to the access of the policy groups
+    pub fn write_path(mut self, path: impl Into<String>) -> Self {
+        self.write_paths.push(path.into());
+        self
+    }
+
+    pub fn read_paths<S: Into<String>>(mut self, paths: impl IntoIterator<Item = S>) -> Self {
+        self.read_paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn write_paths<S: Into<String>>(mut self, paths: impl IntoIterator<Item = S>) -> Self {
+        self.write_paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> Result<AppArmor, PolicyError> {
+        let known_groups = policy_groups(self.policy_version).unwrap_or_default();
+        for group in &self.policy_groups {
+            if !known_groups.iter().any(|known| known.name == group) {
+                return Err(PolicyError::UnknownGroup {
+                    group: group.clone(),
+                    policy_version: self.policy_version.to_owned(),
+                });
+            }
+        }
+        if let Some((group, superseded)) = superseded_group(&self.policy_groups) {
+            return Err(PolicyError::SupersededGroup(
+                group.to_owned(),
+                superseded.to_owned(),
+            ));
+        }
+        if let Some(path) = self
+            .read_paths
+            .iter()
+            .chain(&self.write_paths)
+            .find(|path| !is_valid_policy_path(path))
+        {
+            return Err(PolicyError::InvalidPath(path.clone()));
+        }
+
+        Ok(AppArmor {
+            template: self.template.name().to_owned(),
+            policy_groups: self.policy_groups,
+            policy_version: self.policy_version.to_owned(),
+            read_path: self.read_paths,
+            write_path: self.write_paths,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{policy_groups, PolicyBuilder, PolicyError};
+    use crate::click::Target;
+
+    #[test]
+    fn filters_groups_by_policy_version() {
+        let names = |policy_version| {
+            policy_groups(policy_version)
+                .unwrap()
+                .iter()
+                .map(|group| group.name)
+                .collect::<Vec<_>>()
+        };
+        let xenial = names("16.04");
+        let focal = names("20.04");
+        assert!(xenial.contains(&"friends"));
+        assert!(!focal.contains(&"friends"));
+        assert_eq!(xenial.len(), focal.len() + 1);
+        assert!(policy_groups("1.3").is_none());
+    }
+
+    #[test]
+    fn groups_depend_on_the_policy_version() {
+        let policy = PolicyBuilder::new(Target::Xenial).group("friends").build();
+        assert!(policy
+            .unwrap()
+            .policy_groups
+            .contains(&"friends".to_owned()));
+
+        for target in &[Target::Focal, Target::Noble] {
+            match PolicyBuilder::new(*target).group("friends").build() {
+                Err(PolicyError::UnknownGroup {
+                    group,
+                    policy_version,
+                }) => {
+                    assert_eq!(group, "friends");
+                    assert_eq!(policy_version, "20.04");
+                }
+                other => panic!("friends accepted for {:?}: {:?}", target, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::path::PathBuf;
+
+use deunicode::deunicode;
+
+use super::sanitize::sanitize_text;
+use super::{create_click, namespace, LintMessage, Package, Publishing};
+
+// Apps of a bundle keep their data in "/home/phablet/.local/share/<appname>.<namespace>/<hook>", so
+// the bundle's appname, namespace and the hooks share the length budget of the webapp container's
+// socket path (see `Package::appname`).
+const UNIX_SOCKET_MAX_LEN: usize = 107;
+const APPNAME_MAX_LEN: usize = 16;
+
+/// Several shortcuts distributed as a single click package with a single version. Every shortcut
+/// is installed as an app of its own, with its own desktop entry, AppArmor policy and icons.
+#[derive(Debug)]
+pub struct Bundle {
+    /// The title of the bundle, e.g. "Intranet Suite"
+    pub name: String,
+    /// The shortcuts of the bundle. Their versions and `reproducible` flags are ignored.
+    pub packages: Vec<Package>,
+    /// The version of the bundle. Rebuilds of a bundle need a higher version to install as upgrades.
+    pub version: String,
+    /// Create byte-identical packages for identical inputs by normalizing all archive metadata
+    pub reproducible: bool,
+    /// The store metadata of a published bundle, `None` for an anonymous bundle. The publishing
+    /// metadata of the shortcuts is ignored.
+    pub publishing: Option<Publishing>,
+}
+
+impl Bundle {
+    /// Build the click package, returning its path and warnings about what was left out of it
+    pub fn create(&self) -> Result<(PathBuf, Vec<LintMessage>), Box<dyn Error>> {
+        let target = match self.packages.first() {
+            Some(package) => package.target,
+            None => return Err("A bundle needs at least one shortcut".into()),
+        };
+        if self.packages.iter().any(|package| package.target != target) {
+            return Err("All shortcuts of a bundle need the same target".into());
+        }
+        for package in &self.packages {
+            package.validate()?;
+        }
+
+        let hooks = self.hooks();
+        let package_name = format!("{}.{}", self.appname(), namespace(self.publishing.as_ref()));
+        let mut warnings = Vec::new();
+        let path = create_click(
+            &self.appname(),
+            &self.name,
+            &self.version,
+            target,
+            self.publishing.as_ref(),
+            self.reproducible,
+            |data| {
+                let mut manifest_hooks = BTreeMap::new();
+                for (package, hook) in self.packages.iter().zip(hooks) {
+                    let manifest_hook =
+                        package.write_app(data, &package_name, Some(&hook), &mut warnings)?;
+                    manifest_hooks.insert(hook, manifest_hook);
+                }
+                Ok(manifest_hooks)
+            },
+        )?;
+        Ok((path, warnings))
+    }
+
+    pub fn sanitize(&mut self) {
+        self.name = sanitize_text(&self.name);
+        for package in &mut self.packages {
+            package.sanitize();
+        }
+    }
+
+    /// The name of the bundle's click package without the namespace suffix, derived from its title
+    pub(super) fn appname(&self) -> String {
+        let mut appname = String::new();
+        for c in deunicode(&self.name).to_ascii_lowercase().chars() {
+            if c.is_ascii_alphanumeric() {
+                appname.push(c);
+            } else if !appname.is_empty() && !appname.ends_with('-') {
+                appname.push('-');
+            }
+        }
+        appname.truncate(APPNAME_MAX_LEN);
+        let appname = appname.trim_end_matches('-');
+        if appname.is_empty() {
+            "webapps".to_owned()
+        } else {
+            appname.to_owned()
+        }
+    }
+
+    /// The hook names of the shortcuts, in the same order. Hooks are the shortcuts' appnames,
+    /// shortened to fit the socket path and numbered if they clash.
+    fn hooks(&self) -> Vec<String> {
+        let hook_max_len = (UNIX_SOCKET_MAX_LEN
+            - "/home/phablet/.local/share/.//SingletonSocket".len()
+            - APPNAME_MAX_LEN)
+            .saturating_sub(namespace(self.publishing.as_ref()).len());
+        let mut seen = HashSet::new();
+        self.packages
+            .iter()
+            .map(|package| {
+                let appname = package.appname();
+                let mut hook = shorten(&appname, hook_max_len);
+                let mut n = 1;
+                while !seen.insert(hook.clone()) {
+                    n += 1;
+                    let suffix = format!("-{}", n);
+                    hook = format!(
+                        "{}{}",
+                        shorten(&appname, hook_max_len.saturating_sub(suffix.len())),
+                        suffix
+                    );
+                }
+                hook
+            })
+            .collect()
+    }
+}
+
+/// Cut the ASCII string `s` to at most `len` characters, not ending with a '-'
+fn shorten(s: &str, len: usize) -> String {
+    s[..s.len().min(len)].trim_end_matches('-').to_owned()
+}
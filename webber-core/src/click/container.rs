@@ -0,0 +1,140 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Runtime, Target};
+
+/// The port the webapp container runs the web inspector on if none is given
+pub const DEFAULT_INSPECTOR_PORT: u16 = 9221;
+
+/// Where the webapp container opens links leaving the url patterns
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalLinks {
+    /// In the default browser
+    #[default]
+    Browser,
+    /// In an overlay on top of the app, keeping users in the app
+    Overlay,
+}
+
+impl ExternalLinks {
+    pub const ALL: &'static [ExternalLinks] = &[ExternalLinks::Browser, ExternalLinks::Overlay];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExternalLinks::Browser => "browser",
+            ExternalLinks::Overlay => "overlay",
+        }
+    }
+}
+
+impl FromStr for ExternalLinks {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        ExternalLinks::ALL
+            .iter()
+            .copied()
+            .find(|links| links.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown external link handling \"{}\", expected browser or overlay",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for ExternalLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Options of the webapp container besides its chrome, see `webapp-container --help`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerOptions {
+    /// The program running the app
+    pub runtime: Runtime,
+    /// Reload the page when pulling it down. Only supported by the QML runtime.
+    pub pull_to_refresh: bool,
+    /// Keep session cookies across restarts, so users stay logged in
+    pub store_session_cookies: bool,
+    /// Play audio through media-hub. Ignored on targets without media-hub.
+    pub media_hub_audio: bool,
+    pub external_links: ExternalLinks,
+    /// Allow local content overriding the site to load remote urls
+    pub local_content_remote_access: bool,
+    /// Open the app maximized on desktops
+    pub maximized: bool,
+    /// Run the web inspector on this port, for debugging only
+    pub inspector_port: Option<u16>,
+}
+
+impl Default for ContainerOptions {
+    fn default() -> Self {
+        Self {
+            runtime: Runtime::default(),
+            pull_to_refresh: false,
+            store_session_cookies: true,
+            media_hub_audio: true,
+            external_links: ExternalLinks::default(),
+            local_content_remote_access: false,
+            maximized: false,
+            inspector_port: None,
+        }
+    }
+}
+
+impl ContainerOptions {
+    /// All options disabled, the container's own defaults
+    pub(super) fn disabled() -> Self {
+        Self {
+            store_session_cookies: false,
+            media_hub_audio: false,
+            ..Default::default()
+        }
+    }
+
+    /// The command line arguments of the options for `target`
+    pub(super) fn args(&self, target: Target) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.store_session_cookies {
+            args.push("--store-session-cookies".to_owned());
+        }
+        if self.media_hub_audio && target.media_hub_audio() {
+            args.push("--enable-media-hub-audio".to_owned());
+        }
+        if self.external_links == ExternalLinks::Overlay {
+            args.push("--open-external-url-in-overlay".to_owned());
+        }
+        if self.local_content_remote_access {
+            args.push("--local-content-can-access-remote-urls".to_owned());
+        }
+        if self.maximized {
+            args.push("--maximized".to_owned());
+        }
+        if let Some(port) = self.inspector_port {
+            args.push(format!("--inspector={}", port));
+        }
+        args
+    }
+
+    /// Apply the command line argument `arg`, returns false if it isn't one of the options
+    pub(super) fn parse_arg(&mut self, arg: &str) -> bool {
+        match arg {
+            "--store-session-cookies" => self.store_session_cookies = true,
+            "--enable-media-hub-audio" => self.media_hub_audio = true,
+            "--open-external-url-in-overlay" => self.external_links = ExternalLinks::Overlay,
+            "--local-content-can-access-remote-urls" => self.local_content_remote_access = true,
+            "--maximized" => self.maximized = true,
+            "--inspector" => self.inspector_port = Some(DEFAULT_INSPECTOR_PORT),
+            _ => match arg.strip_prefix("--inspector=") {
+                Some(port) => {
+                    self.inspector_port = Some(port.parse().unwrap_or(DEFAULT_INSPECTOR_PORT))
+                }
+                None => return false,
+            },
+        }
+        true
+    }
+}
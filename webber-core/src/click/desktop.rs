@@ -0,0 +1,394 @@
+use std::fmt;
+
+/// The name of the main group of a desktop entry
+pub const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+
+/// A desktop entry file, see https://specifications.freedesktop.org/desktop-entry-spec/latest/.
+/// Groups and keys keep their order, values are stored escaped as they appear in the file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+    groups: Vec<DesktopGroup>,
+}
+
+/// A group of a desktop entry, e.g. `[Desktop Entry]` or `[Desktop Action <id>]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopGroup {
+    name: String,
+    keys: Vec<(String, String)>,
+}
+
+impl DesktopEntry {
+    /// Parse a desktop file. Lines that are neither group headers nor keys are skipped, use
+    /// `lint` to find them.
+    pub fn parse(content: &str) -> Self {
+        let mut entry = DesktopEntry::default();
+        let mut group = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                group = Some(name.to_owned());
+                entry.group_mut(name);
+            } else if let (Some(group), Some((key, value))) = (&group, line.split_once('=')) {
+                entry
+                    .group_mut(group)
+                    .set_raw(key.trim(), value.trim_start().to_owned());
+            }
+        }
+
+        entry
+    }
+
+    pub fn group(&self, name: &str) -> Option<&DesktopGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// The group `name`, appended to the entry if it doesn't exist yet
+    pub fn group_mut(&mut self, name: &str) -> &mut DesktopGroup {
+        match self.groups.iter().position(|group| group.name == name) {
+            Some(idx) => &mut self.groups[idx],
+            None => {
+                self.groups.push(DesktopGroup {
+                    name: name.to_owned(),
+                    keys: Vec::new(),
+                });
+                self.groups.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &DesktopGroup> {
+        self.groups.iter()
+    }
+}
+
+impl fmt::Display for DesktopEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, group) in self.groups.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "[{}]", group.name)?;
+            for (key, value) in &group.keys {
+                writeln!(f, "{}={}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DesktopGroup {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The unescaped string value of `key`
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.get_raw(key).map(unescape_value)
+    }
+
+    /// The value of `key` as it appears in the file
+    pub fn get_raw(&self, key: &str) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The unescaped items of the list value of `key`, e.g. of `Actions`
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        let raw = match self.get_raw(key) {
+            Some(raw) => raw,
+            None => return Vec::new(),
+        };
+        let mut items = Vec::new();
+        let mut item = String::new();
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(';') => item.push(';'),
+                    Some(escaped) => {
+                        item.push('\\');
+                        item.push(escaped);
+                    }
+                    None => item.push('\\'),
+                },
+                ';' => items.push(unescape_value(&std::mem::take(&mut item))),
+                c => item.push(c),
+            }
+        }
+        if !item.is_empty() {
+            items.push(unescape_value(&item));
+        }
+        items
+    }
+
+    /// The localized values of `key` by locale, e.g. `("de", "Rechner")` for `Name[de]=Rechner`
+    pub fn get_localized(&self, key: &str) -> Vec<(String, String)> {
+        self.keys
+            .iter()
+            .filter_map(|(other, value)| {
+                let locale = other
+                    .strip_prefix(key)?
+                    .strip_prefix('[')?
+                    .strip_suffix(']')?;
+                Some((locale.to_owned(), unescape_value(value)))
+            })
+            .collect()
+    }
+
+    /// Set the string value of `key`, escaping it
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        self.set_raw(key, escape_value(value))
+    }
+
+    /// Set the value of the localized key `key[locale]`
+    pub fn set_localized(&mut self, key: &str, locale: &str, value: &str) -> &mut Self {
+        self.set(&format!("{}[{}]", key, locale), value)
+    }
+
+    /// Set a list value, e.g. `Actions=a;b;`
+    pub fn set_list<S: AsRef<str>>(&mut self, key: &str, items: &[S]) -> &mut Self {
+        let value = items
+            .iter()
+            .map(|item| format!("{};", escape_value(item.as_ref()).replace(';', "\\;")))
+            .collect::<String>();
+        self.set_raw(key, value)
+    }
+
+    /// Set a command line like `Exec`, quoting each argument
+    pub fn set_exec<S: AsRef<str>>(&mut self, key: &str, args: &[S]) -> &mut Self {
+        let exec = args
+            .iter()
+            .map(|arg| quote_exec_arg(arg.as_ref()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.set(key, &exec)
+    }
+
+    fn set_raw(&mut self, key: &str, value: String) -> &mut Self {
+        match self.keys.iter_mut().find(|(other, _)| other == key) {
+            Some((_, old)) => *old = value,
+            None => self.keys.push((key.to_owned(), value)),
+        }
+        self
+    }
+}
+
+/// Quote an argument of the `Exec` key. Arguments containing reserved characters are quoted
+/// with double quotes, `%` is escaped as it introduces field codes.
+fn quote_exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    if !arg.is_empty() && !arg.chars().any(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if "\"`$\\".contains(c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Split the unescaped value of the `Exec` key into its arguments, resolving quotes and `%%`.
+/// Other field codes are kept. Arguments in single quotes, written by earlier versions of Webber,
+/// are accepted as well.
+pub fn split_exec(exec: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = None::<String>;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => arg.push(escaped),
+                            None => return Err("Unterminated escape sequence".to_owned()),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err("Unterminated quote".to_owned()),
+                    }
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err("Unterminated quote".to_owned()),
+                    }
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(arg) = current {
+        args.push(arg);
+    }
+
+    Ok(args.into_iter().map(|arg| arg.replace("%%", "%")).collect())
+}
+
+/// Escape a string value. Leading and trailing spaces are escaped as parsers trim them.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+    let last = value.chars().count().saturating_sub(1);
+    for (idx, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ' ' if idx == 0 || idx == last => escaped.push_str("\\s"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Resolve the escape sequences of a string value. Unknown escape sequences are kept.
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `set` to a desktop file and parse it again
+    fn round_trip(set: impl FnOnce(&mut DesktopGroup)) -> DesktopGroup {
+        let mut entry = DesktopEntry::default();
+        set(entry.group_mut(DESKTOP_ENTRY_GROUP));
+        let parsed = DesktopEntry::parse(&entry.to_string());
+        assert_eq!(parsed, entry);
+        parsed.group(DESKTOP_ENTRY_GROUP).unwrap().clone()
+    }
+
+    #[test]
+    fn round_trips_exec_args() {
+        let args = [
+            "webapp-container",
+            "--app-id=a.b_c",
+            "100%",
+            "%u",
+            "say \"hi\"",
+            "C:\\path\\",
+            "$HOME",
+            "`id`",
+            "",
+            "a b",
+            "it's",
+        ];
+        let group = round_trip(|group| {
+            group.set_exec("Exec", &args);
+        });
+        assert_eq!(
+            group.get("Exec").unwrap(),
+            r#"webapp-container --app-id=a.b_c 100%% %%u "say \"hi\"" "C:\\path\\" "\$HOME" "\`id\`" "" "a b" "it's""#
+        );
+        assert_eq!(split_exec(&group.get("Exec").unwrap()).unwrap(), args);
+    }
+
+    #[test]
+    fn splits_exec_with_field_codes_and_legacy_quotes() {
+        assert_eq!(
+            split_exec("webapp-container 'https://a.b/?q=1 2' %u").unwrap(),
+            ["webapp-container", "https://a.b/?q=1 2", "%u"]
+        );
+        assert_eq!(split_exec("a  'it''s'\"\" ''").unwrap(), ["a", "its", ""]);
+        assert!(split_exec("a \"b").is_err());
+        assert!(split_exec("a 'b").is_err());
+        assert!(split_exec("a \"b\\").is_err());
+    }
+
+    #[test]
+    fn round_trips_values() {
+        let values = [
+            " leading",
+            "trailing ",
+            "  both  ",
+            " ",
+            "line\nbreak\n",
+            "\ttab\r",
+            "back\\slash\\s",
+            "",
+        ];
+        for value in &values {
+            let group = round_trip(|group| {
+                group.set("Comment", value);
+            });
+            assert_eq!(&group.get("Comment").unwrap(), value);
+        }
+        assert_eq!(escape_value(" a b "), "\\sa b\\s");
+        assert_eq!(escape_value("a\nb\\"), "a\\nb\\\\");
+    }
+
+    #[test]
+    fn round_trips_lists() {
+        let items = ["a;b", "c\\;d", ";", " e ", "f\\", "g\nh"];
+        let group = round_trip(|group| {
+            group.set_list("Keywords", &items);
+        });
+        assert_eq!(group.get_list("Keywords"), items);
+
+        let group = DesktopEntry::parse("[Desktop Entry]\nActions=a;b\\;c;d");
+        let group = group.group(DESKTOP_ENTRY_GROUP).unwrap();
+        assert_eq!(group.get_list("Actions"), ["a", "b;c", "d"]);
+        assert!(group.get_list("Missing").is_empty());
+    }
+
+    #[test]
+    fn reads_localized_values() {
+        let group = round_trip(|group| {
+            group
+                .set("Name", "Calculator")
+                .set_localized("Name", "de", "Rechner")
+                .set_localized("Name", "sr@latin", " Kalkulator");
+        });
+        assert_eq!(
+            group.get_localized("Name"),
+            [
+                ("de".to_owned(), "Rechner".to_owned()),
+                ("sr@latin".to_owned(), " Kalkulator".to_owned())
+            ]
+        );
+    }
+}
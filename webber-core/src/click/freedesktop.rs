@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::{
+    detect_image_type, write_file, DesktopEntry, LintMessage, Package, DESKTOP_ENTRY_GROUP,
+};
+
+/// Browsers that can run a web app in its own window on regular Linux desktops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    /// `chromium --app=<url>`
+    Chromium,
+    /// `epiphany --application-mode` with a separate profile per shortcut
+    Epiphany,
+    /// `firefox --kiosk <url>`
+    Firefox,
+}
+
+impl FromStr for Launcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "chromium" => Ok(Launcher::Chromium),
+            "epiphany" => Ok(Launcher::Epiphany),
+            "firefox" => Ok(Launcher::Firefox),
+            _ => Err(format!(
+                "Unknown launcher \"{}\", expected chromium, epiphany or firefox",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Launcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Launcher::Chromium => write!(f, "chromium"),
+            Launcher::Epiphany => write!(f, "epiphany"),
+            Launcher::Firefox => write!(f, "firefox"),
+        }
+    }
+}
+
+impl Package {
+    /// Install the shortcut as a freedesktop desktop entry launching `launcher` into
+    /// `$XDG_DATA_HOME/applications` and its icon into the hicolor icon theme. Returns the path of
+    /// the desktop file and warnings about what was left out.
+    pub fn install_desktop_entry(
+        &self,
+        launcher: Launcher,
+    ) -> Result<(PathBuf, Vec<LintMessage>), Box<dyn Error>> {
+        self.validate()?;
+        let xdg_dirs = xdg::BaseDirectories::new()?;
+        let data_home = xdg_dirs.get_data_home();
+        let id = format!("webber-{}", self.appname());
+
+        let build_dir = xdg_dirs
+            .get_cache_home()
+            .join("webber.timsueberkrueb/desktop-build");
+        fs::create_dir_all(&build_dir)?;
+        fs::remove_dir_all(&build_dir)?;
+        fs::create_dir(&build_dir)?;
+
+        let mut warnings = Vec::new();
+        let icon_filename = self.embed_icon(&build_dir, &mut warnings)?;
+        let icon = install_icon(&build_dir.join(icon_filename), &data_home, &id)?;
+
+        let mut exec = vec![launcher.to_string()];
+        match launcher {
+            Launcher::Chromium => {
+                exec.push(format!("--app={}", self.url));
+                exec.push(format!("--class={}", id));
+                if !self.user_agent.is_empty() {
+                    exec.push(format!("--user-agent={}", self.user_agent));
+                }
+            }
+            Launcher::Epiphany => {
+                // Epiphany only runs in application mode with a profile directory that is
+                // marked as a web app profile
+                let profile = data_home.join("webber.timsueberkrueb/epiphany").join(&id);
+                fs::create_dir_all(&profile)?;
+                write_file(&profile.join(".app"), "")?;
+                exec.push("--application-mode".to_owned());
+                exec.push(format!("--profile={}", profile.display()));
+                exec.push(self.url.clone());
+            }
+            Launcher::Firefox => {
+                exec.push("--kiosk".to_owned());
+                exec.push(self.url.clone());
+            }
+        }
+
+        let mut entry = DesktopEntry::default();
+        let group = entry.group_mut(DESKTOP_ENTRY_GROUP);
+        group.set("Name", &self.name);
+        for name in &self.names {
+            group.set_localized("Name", &name.locale, &name.value());
+        }
+        group
+            .set_exec("Exec", &exec)
+            .set("Icon", &icon)
+            .set("Terminal", "false")
+            .set("Type", "Application")
+            .set_list("Categories", &["Network", "WebBrowser"])
+            .set("StartupWMClass", &id)
+            .set("X-Webber-Theme-Color", &self.theme_color);
+
+        let applications = data_home.join("applications");
+        fs::create_dir_all(&applications)?;
+        let desktop_path = applications.join(format!("{}.desktop", id));
+
+        write_file(&desktop_path, &entry.to_string())?;
+
+        Ok((desktop_path, warnings))
+    }
+}
+
+/// Install the icon at `src` into the hicolor icon theme as `id` and return the value for the
+/// `Icon` key. Icon themes only support PNG and SVG icons, other images are referenced by their
+/// absolute path instead.
+fn install_icon(src: &Path, data_home: &Path, id: &str) -> Result<String, Box<dyn Error>> {
+    let data = fs::read(src)?;
+    let icons = data_home.join("icons/hicolor");
+
+    let target = match (detect_image_type(&data), png_size(&data)) {
+        (Some("svg"), _) => icons.join("scalable/apps").join(format!("{}.svg", id)),
+        (Some("png"), Some((width, height))) if width == height => icons
+            .join(format!("{}x{}/apps", width, height))
+            .join(format!("{}.png", id)),
+        (ext, _) => {
+            let target = data_home.join("webber.timsueberkrueb/icons").join(format!(
+                "{}.{}",
+                id,
+                ext.unwrap_or("img")
+            ));
+            fs::create_dir_all(target.parent().unwrap())?;
+            fs::write(&target, &data)?;
+            return Ok(target.to_string_lossy().into_owned());
+        }
+    };
+
+    fs::create_dir_all(target.parent().unwrap())?;
+    fs::write(&target, &data)?;
+    Ok(id.to_owned())
+}
+
+/// Read the dimensions from the header of a PNG image
+fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 24 || &data[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+    let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+    Some((width, height))
+}
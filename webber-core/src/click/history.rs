@@ -0,0 +1,122 @@
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{read, Bundle, Package, ReadPackageError, INITIAL_VERSION};
+
+/// The most recently built package of every shortcut, used to version rebuilds of a shortcut as
+/// upgrades of the previous build.
+pub struct BuildHistory {
+    dir: PathBuf,
+}
+
+impl BuildHistory {
+    /// Open the history, its directory is created once the first build is recorded
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let dir = xdg::BaseDirectories::new()?
+            .get_data_home()
+            .join("webber.timsueberkrueb/builds");
+        Ok(Self { dir })
+    }
+
+    /// The version for a new build of `package`, i.e. the initial version if the shortcut was
+    /// never built before or the successor of the version of its previous build. Fails if the
+    /// previous build can't be read, as guessing the version would break upgrades.
+    pub fn next_version(&self, package: &Package) -> Result<String, Box<dyn Error>> {
+        self.next_version_of(&package.appname())
+    }
+
+    /// The version for a new build of `bundle`, see `next_version`
+    pub fn next_bundle_version(&self, bundle: &Bundle) -> Result<String, Box<dyn Error>> {
+        self.next_version_of(&bundle.appname())
+    }
+
+    /// Remember `click_path` as the most recent build of `package`
+    pub fn record(&self, package: &Package, click_path: &Path) -> io::Result<()> {
+        self.record_as(&package.appname(), click_path)
+    }
+
+    /// Remember `click_path` as the most recent build of `bundle`
+    pub fn record_bundle(&self, bundle: &Bundle, click_path: &Path) -> io::Result<()> {
+        self.record_as(&bundle.appname(), click_path)
+    }
+
+    fn next_version_of(&self, appname: &str) -> Result<String, Box<dyn Error>> {
+        let path = self.path_for(appname);
+        match read(&path) {
+            Ok(previous) => Ok(next_version(&previous.package.version)),
+            Err(ReadPackageError::IoError(err)) if err.kind() == io::ErrorKind::NotFound => {
+                Ok(INITIAL_VERSION.to_owned())
+            }
+            Err(err) => Err(format!(
+                "Failed to read the previous build {}: {}",
+                path.display(),
+                err
+            )
+            .into()),
+        }
+    }
+
+    fn record_as(&self, appname: &str, click_path: &Path) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::copy(click_path, self.path_for(appname))?;
+        Ok(())
+    }
+
+    fn path_for(&self, appname: &str) -> PathBuf {
+        self.dir.join(format!("{}.click", appname))
+    }
+}
+
+impl Package {
+    /// Make this package an upgrade of the previously built `path`, which has to be a build of
+    /// the same shortcut. A published package keeps its publishing metadata unless replaced.
+    pub fn upgrade_from(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let previous = read(path)?;
+        if previous.appname != self.appname() {
+            return Err(format!(
+                "{} is a build of a different shortcut ({})",
+                path.display(),
+                previous.appname
+            )
+            .into());
+        }
+        self.version = next_version(&previous.package.version);
+        if self.publishing.is_none() {
+            self.publishing = previous.package.publishing;
+        }
+        Ok(())
+    }
+}
+
+/// Increment the last numeric component of `version`, e.g. `1.0.9` becomes `1.0.10`
+pub fn next_version(version: &str) -> String {
+    let (prefix, last) = match version.rsplit_once('.') {
+        Some((prefix, last)) => (format!("{}.", prefix), last),
+        None => (String::new(), version),
+    };
+    match last.parse::<u64>() {
+        Ok(n) => format!("{}{}", prefix, n + 1),
+        Err(_) => format!("{}.1", version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{BuildHistory, INITIAL_VERSION};
+
+    #[test]
+    fn only_missing_builds_start_at_the_initial_version() {
+        let dir = std::env::temp_dir().join(format!("webber-history-{}", std::process::id()));
+        let history = BuildHistory { dir: dir.clone() };
+        assert_eq!(history.next_version_of("site").unwrap(), INITIAL_VERSION);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(history.path_for("site"), b"not a click").unwrap();
+        assert!(history.next_version_of("site").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,514 @@
+use csscolorparser::Color;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, ImageOutputFormat, Rgba, RgbaImage};
+use scraper::{Html, Node};
+
+/// The edge length of embedded raster icons. Lomiri renders app icons from 256px sources in the
+/// app grid, launcher and splash screen, so this is what click packages are expected to ship.
+pub const ICON_SIZE: u32 = 256;
+
+/// The share of a maskable icon's edge length covered by its safe zone, see
+/// https://w3c.github.io/manifest/#icon-masks
+const MASKABLE_SAFE_ZONE: f64 = 0.8;
+
+/// An icon ready to be embedded into a package
+#[derive(Debug)]
+pub struct NormalizedIcon {
+    /// The file extension matching the content, either `png` or `svg`
+    pub extension: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl NormalizedIcon {
+    pub fn filename(&self) -> String {
+        format!("icon.{}", self.extension)
+    }
+}
+
+/// Convert an icon of any supported format into a square `ICON_SIZE` PNG, or a sanitized SVG
+/// for vector icons. Non-square images are centered on a transparent background.
+pub fn normalize_icon(data: &[u8]) -> Result<NormalizedIcon, String> {
+    match detect_image_type(data) {
+        Some("svg") => {
+            let svg = std::str::from_utf8(data).map_err(|_| "SVG is not valid UTF-8")?;
+            Ok(NormalizedIcon {
+                extension: "svg",
+                data: sanitize_svg(svg)?.svg.into_bytes(),
+            })
+        }
+        Some(_) => Ok(NormalizedIcon {
+            extension: "png",
+            data: encode_png(&fit_square(&decode(data)?, ICON_SIZE))?,
+        }),
+        None => Err("Unsupported icon format".to_owned()),
+    }
+}
+
+/// Crop a maskable icon to its safe zone and composite it onto `background`. Lomiri applies its
+/// own shape to app icons, so the padding outside of the safe zone would only shrink the icon.
+/// SVG icons can't be cropped and are normalized as is.
+pub fn normalize_maskable_icon(data: &[u8], background: &Color) -> Result<NormalizedIcon, String> {
+    if detect_image_type(data) == Some("svg") {
+        return normalize_icon(data);
+    }
+
+    let image = decode(data)?;
+    let (width, height) = image.dimensions();
+    let safe_width = ((width as f64 * MASKABLE_SAFE_ZONE) as u32).max(1);
+    let safe_height = ((height as f64 * MASKABLE_SAFE_ZONE) as u32).max(1);
+    let safe_zone = image.crop_imm(
+        (width - safe_width) / 2,
+        (height - safe_height) / 2,
+        safe_width,
+        safe_height,
+    );
+
+    let (r, g, b, _) = background.rgba_u8();
+    let mut canvas = RgbaImage::from_pixel(ICON_SIZE, ICON_SIZE, Rgba([r, g, b, 255]));
+    imageops::overlay(&mut canvas, &fit_square(&safe_zone, ICON_SIZE), 0, 0);
+
+    Ok(NormalizedIcon {
+        extension: "png",
+        data: encode_png(&canvas)?,
+    })
+}
+
+/// Fill a monochrome icon with `color`. Only the alpha channel of monochrome icons is meaningful.
+pub fn tint_monochrome_icon(data: &[u8], color: &Color) -> Result<NormalizedIcon, String> {
+    if detect_image_type(data) == Some("svg") {
+        return Err("Monochrome SVG icons can't be tinted".to_owned());
+    }
+
+    let mut icon = fit_square(&decode(data)?, ICON_SIZE);
+    let (r, g, b, _) = color.rgba_u8();
+    for pixel in icon.pixels_mut() {
+        pixel.0 = [r, g, b, pixel.0[3]];
+    }
+
+    Ok(NormalizedIcon {
+        extension: "png",
+        data: encode_png(&icon)?,
+    })
+}
+
+fn decode(data: &[u8]) -> Result<DynamicImage, String> {
+    image::load_from_memory(data).map_err(|err| format!("Failed to decode icon: {}", err))
+}
+
+/// Scale `image` to fit into a `size`×`size` square, keeping its aspect ratio
+fn fit_square(image: &DynamicImage, size: u32) -> RgbaImage {
+    let scaled = image.resize(size, size, FilterType::Lanczos3).to_rgba8();
+    let mut canvas = RgbaImage::new(size, size);
+    let x = (size - scaled.width()) / 2;
+    let y = (size - scaled.height()) / 2;
+    imageops::overlay(&mut canvas, &scaled, x, y);
+    canvas
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(image.clone())
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|err| format!("Failed to encode icon: {}", err))?;
+    Ok(png)
+}
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+/// The SVG elements kept in icons, everything else is removed along with its content
+const SVG_ELEMENTS: &[&str] = &[
+    "svg",
+    "g",
+    "defs",
+    "symbol",
+    "use",
+    "title",
+    "desc",
+    "style",
+    "path",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "text",
+    "tspan",
+    "textPath",
+    "image",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+    "clipPath",
+    "mask",
+    "pattern",
+    "filter",
+    "feBlend",
+    "feColorMatrix",
+    "feComposite",
+    "feDropShadow",
+    "feFlood",
+    "feGaussianBlur",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+];
+
+/// The attributes kept in icons, as named by the HTML parser
+const SVG_ATTRIBUTES: &[&str] = &[
+    "id",
+    "class",
+    "style",
+    "version",
+    "width",
+    "height",
+    "viewBox",
+    "preserveAspectRatio",
+    "transform",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "fx",
+    "fy",
+    "dx",
+    "dy",
+    "d",
+    "points",
+    "pathLength",
+    "rotate",
+    "color",
+    "display",
+    "visibility",
+    "overflow",
+    "opacity",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "stroke",
+    "stroke-width",
+    "stroke-opacity",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "clip-path",
+    "clip-rule",
+    "clipPathUnits",
+    "mask",
+    "maskUnits",
+    "maskContentUnits",
+    "filter",
+    "filterUnits",
+    "primitiveUnits",
+    "gradientUnits",
+    "gradientTransform",
+    "spreadMethod",
+    "offset",
+    "stop-color",
+    "stop-opacity",
+    "patternUnits",
+    "patternContentUnits",
+    "patternTransform",
+    "marker-start",
+    "marker-mid",
+    "marker-end",
+    "markerWidth",
+    "markerHeight",
+    "markerUnits",
+    "refX",
+    "refY",
+    "orient",
+    "font-family",
+    "font-size",
+    "font-style",
+    "font-weight",
+    "text-anchor",
+    "dominant-baseline",
+    "letter-spacing",
+    "in",
+    "in2",
+    "result",
+    "stdDeviation",
+    "mode",
+    "operator",
+    "k1",
+    "k2",
+    "k3",
+    "k4",
+    "type",
+    "values",
+    "radius",
+    "flood-color",
+    "flood-opacity",
+    "mix-blend-mode",
+    "isolation",
+    "href",
+];
+
+/// An SVG reduced to the elements and attributes safe to render
+#[derive(Debug)]
+pub struct SanitizedSvg {
+    pub svg: String,
+    /// Whether elements or attributes were removed, e.g. scripts or external references
+    pub removed_content: bool,
+}
+
+/// Parse an SVG and serialize it again with only the elements and attributes of an allowlist.
+/// Nothing that could execute code or load external resources survives: scripts, animations,
+/// event handlers, entity declarations and references other than to fragments or embedded
+/// raster images.
+pub fn sanitize_svg(svg: &str) -> Result<SanitizedSvg, String> {
+    let html = Html::parse_fragment(svg);
+    let root = html
+        .tree
+        .root()
+        .descendants()
+        .find(|node| match node.value() {
+            Node::Element(element) => {
+                &*element.name.ns == SVG_NAMESPACE && &*element.name.local == "svg"
+            }
+            _ => false,
+        })
+        .ok_or("SVG has no svg element")?;
+
+    let mut sanitized = SanitizedSvg {
+        svg: String::new(),
+        removed_content: false,
+    };
+    write_svg_node(root, &mut sanitized, true);
+    Ok(sanitized)
+}
+
+fn write_svg_node(node: ego_tree::NodeRef<Node>, out: &mut SanitizedSvg, root: bool) {
+    match node.value() {
+        Node::Element(element) => {
+            let name = &*element.name.local;
+            let style = name == "style";
+            if &*element.name.ns != SVG_NAMESPACE
+                || !SVG_ELEMENTS.contains(&name)
+                || (style && !is_safe_css(&node_text(node)))
+            {
+                out.removed_content = true;
+                return;
+            }
+
+            out.svg.push('<');
+            out.svg.push_str(name);
+            if root {
+                out.svg.push_str(&format!(
+                    " xmlns=\"{}\" xmlns:xlink=\"{}\"",
+                    SVG_NAMESPACE, XLINK_NAMESPACE
+                ));
+            }
+            // Sorted, as the parser doesn't keep the order and icons should be reproducible
+            let mut attrs = element.attrs.iter().collect::<Vec<_>>();
+            attrs.sort_by(|(a, _), (b, _)| (&*a.ns, &*a.local).cmp(&(&*b.ns, &*b.local)));
+            for (attr, value) in attrs {
+                let name = match &*attr.ns {
+                    "" => &*attr.local,
+                    XLINK_NAMESPACE if &*attr.local == "href" => "xlink:href",
+                    // The namespaces are declared on the root element
+                    XMLNS_NAMESPACE => continue,
+                    _ => {
+                        out.removed_content = true;
+                        continue;
+                    }
+                };
+                let allowed = SVG_ATTRIBUTES.contains(&&*attr.local)
+                    && match &*attr.local {
+                        "href" => is_safe_reference(value),
+                        _ => is_safe_css(value),
+                    };
+                if allowed {
+                    out.svg
+                        .push_str(&format!(" {}=\"{}\"", name, escape_xml(value)));
+                } else {
+                    out.removed_content = true;
+                }
+            }
+
+            if node.has_children() {
+                out.svg.push('>');
+                for child in node.children() {
+                    write_svg_node(child, out, false);
+                }
+                out.svg.push_str(&format!("</{}>", name));
+            } else {
+                out.svg.push_str("/>");
+            }
+        }
+        Node::Text(text) => out.svg.push_str(&escape_xml(text)),
+        // Comments and processing instructions
+        _ => {}
+    }
+}
+
+fn node_text(node: ego_tree::NodeRef<Node>) -> String {
+    node.descendants()
+        .filter_map(|node| node.value().as_text())
+        .map(|text| &**text)
+        .collect()
+}
+
+/// References may only point to fragments of the icon itself or to embedded raster images
+fn is_safe_reference(target: &str) -> bool {
+    let target = target.trim();
+    target.starts_with('#')
+        || [
+            "data:image/png",
+            "data:image/jpeg",
+            "data:image/gif",
+            "data:image/webp",
+        ]
+        .iter()
+        .any(|prefix| target.starts_with(prefix))
+}
+
+/// Reject CSS (and attribute values containing CSS) which could load resources. `url()` may
+/// only reference fragments, escapes are rejected as they could hide a `url(` or `@import`.
+fn is_safe_css(css: &str) -> bool {
+    let css = css.to_ascii_lowercase();
+    !css.contains('\\')
+        && !css.contains('@')
+        && !css.contains("expression")
+        && css.match_indices("url(").all(|(start, _)| {
+            css[start + 4..]
+                .trim_start_matches(|c: char| c.is_whitespace() || c == '"' || c == '\'')
+                .starts_with('#')
+        })
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Detect the type of an image from its content and return the usual file extension
+pub fn detect_image_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if data.starts_with(b"\xff\xd8\xff") {
+        Some("jpg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if data.starts_with(b"\x00\x00\x01\x00") {
+        Some("ico")
+    } else if data.starts_with(b"BM") {
+        Some("bmp")
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("webp")
+    } else if String::from_utf8_lossy(&data[..data.len().min(1024)]).contains("<svg") {
+        Some("svg")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_svg;
+
+    fn sanitize(svg: &str) -> String {
+        let sanitized = sanitize_svg(svg).unwrap();
+        assert!(sanitized.removed_content, "nothing removed from {}", svg);
+        sanitized.svg
+    }
+
+    #[test]
+    fn keeps_safe_svgs() {
+        let svg = r##"<?xml version="1.0"?>
+<!-- Icon -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 16 16">
+  <defs><linearGradient id="g"><stop offset="0" stop-color="#fff"/></linearGradient></defs>
+  <path d="M0 0h16v16z" fill="url(#g)" style="stroke: url('#g')"/>
+  <use xlink:href="#g"/><text>A &amp; B</text>
+</svg>"##;
+        let sanitized = sanitize_svg(svg).unwrap();
+        assert!(!sanitized.removed_content);
+        assert_eq!(
+            sanitized.svg,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 16 16">
+  <defs><linearGradient id="g"><stop offset="0" stop-color="#fff"/></linearGradient></defs>
+  <path d="M0 0h16v16z" fill="url(#g)" style="stroke: url('#g')"/>
+  <use xlink:href="#g"/><text>A &amp; B</text>
+</svg>"##
+        );
+    }
+
+    #[test]
+    fn removes_scripts() {
+        for svg in &[
+            "<svg><script>alert(1)</script><rect/></svg>",
+            "<svg><svg:script>alert(1)</svg:script><rect/></svg>",
+            "<svg><rect/><script>alert(1)",
+            "<svg><foreignObject><iframe src=\"https://example.com\"/></foreignObject></svg>",
+        ] {
+            let svg = sanitize(svg);
+            assert!(!svg.contains("script") && !svg.contains("alert"), "{}", svg);
+            assert!(!svg.contains("iframe"), "{}", svg);
+        }
+    }
+
+    #[test]
+    fn removes_animations_and_handlers() {
+        for svg in &[
+            "<svg><a><set attributeName=\"href\" to=\"javascript:alert(1)\"/></a></svg>",
+            "<svg><rect><animate attributeName=\"onclick\" values=\"alert(1)\"/></rect></svg>",
+            "<svg onload=\"alert(1)\"><rect ONCLICK=\"alert(1)\"/></svg>",
+        ] {
+            let svg = sanitize(svg);
+            assert!(!svg.contains("alert"), "{}", svg);
+        }
+    }
+
+    #[test]
+    fn removes_external_references() {
+        for svg in &[
+            "<svg><rect style=\"fill: url(javascript:alert(1))\"/></svg>",
+            "<svg><rect style=\"fill: u\\72l(https://example.com/)\"/></svg>",
+            "<svg><rect fill=\"url( 'https://example.com/#a')\"/></svg>",
+            "<svg><style>@import 'https://example.com/style.css';</style></svg>",
+            "<svg><image href=\"https://example.com/image.png\"/></svg>",
+            "<svg><use xlink:href=\"data:image/svg+xml;base64,PHN2Zz48L3N2Zz4=\"/></svg>",
+        ] {
+            let svg = sanitize(svg);
+            assert!(
+                !svg.contains("example.com") && !svg.contains("alert"),
+                "{}",
+                svg
+            );
+            assert!(!svg.contains("svg+xml"), "{}", svg);
+        }
+    }
+
+    #[test]
+    fn ignores_entity_declarations() {
+        let svg = sanitize_svg(
+            r#"<!DOCTYPE svg [<!ENTITY x "<script>alert(1)</script>">]><svg><text>&x;</text></svg>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            svg.svg,
+            format!(
+                "<svg xmlns=\"{}\" xmlns:xlink=\"{}\"><text>&amp;x;</text></svg>",
+                super::SVG_NAMESPACE,
+                super::XLINK_NAMESPACE
+            )
+        );
+    }
+}
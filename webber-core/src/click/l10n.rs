@@ -0,0 +1,106 @@
+use language_tags::LanguageTag;
+
+use super::DesktopGroup;
+
+/// Makes the shell lay out a name right-to-left, even if it starts with a left-to-right word such
+/// as a brand name
+const RIGHT_TO_LEFT_MARK: char = '\u{200f}';
+
+/// Primary language subtags of languages written right-to-left
+const RTL_LANGUAGES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ks", "ku", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+/// A translation of the package name, written to the `Name[<locale>]` key of the desktop entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedName {
+    /// The locale in the format of desktop entries, e.g. `pt_BR` or `sr@latin`
+    pub locale: String,
+    pub name: String,
+    /// Whether the name is written right-to-left
+    pub rtl: bool,
+}
+
+impl LocalizedName {
+    /// Create a translation for `lang`, a language tag like `pt-BR` or a desktop entry locale like
+    /// `pt_BR`. The name is written right-to-left if `rtl` says so, or if it isn't given and
+    /// `lang` is a right-to-left language.
+    pub fn new(lang: &str, name: String, rtl: Option<bool>) -> Result<Self, String> {
+        let locale = desktop_locale(lang)?;
+        let rtl = rtl.unwrap_or_else(|| is_rtl_language(&locale));
+        Ok(Self { locale, name, rtl })
+    }
+
+    /// The value of the `Name[<locale>]` key
+    pub(super) fn value(&self) -> String {
+        if self.rtl {
+            format!("{}{}", RIGHT_TO_LEFT_MARK, self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Read the translations from the `Name[<locale>]` keys of a desktop entry, ordered by locale
+    pub(super) fn from_desktop_entry(entry: &DesktopGroup) -> Vec<Self> {
+        let mut names = entry
+            .get_localized("Name")
+            .into_iter()
+            .map(|(locale, value)| {
+                let (name, rtl) = match value.strip_prefix(RIGHT_TO_LEFT_MARK) {
+                    Some(name) => (name, true),
+                    None => (value.as_str(), false),
+                };
+                Self {
+                    locale,
+                    name: name.to_owned(),
+                    rtl,
+                }
+            })
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| a.locale.cmp(&b.locale));
+        names
+    }
+}
+
+/// Convert a language tag like `pt-BR` or `sr-Latn` into the locale format of desktop entries
+/// (`lang_COUNTRY@MODIFIER`), e.g. `pt_BR` or `sr@latin`. Locales already in that format are
+/// normalized.
+pub fn desktop_locale(lang: &str) -> Result<String, String> {
+    let (tag, modifier) = match lang.trim().split_once('@') {
+        Some((tag, modifier)) => (tag, Some(modifier.to_ascii_lowercase())),
+        None => (lang.trim(), None),
+    };
+    let tag = LanguageTag::parse(&tag.replace('_', "-"))
+        .map_err(|err| format!("Invalid language \"{}\": {}", lang, err))?;
+
+    let mut locale = tag.primary_language().to_ascii_lowercase();
+    if let Some(region) = tag.region() {
+        locale.push('_');
+        locale.push_str(&region.to_ascii_uppercase());
+    }
+    // glibc names script variants by modifiers
+    let modifier = modifier.or_else(|| {
+        tag.script()
+            .map(|script| match script.to_ascii_lowercase().as_str() {
+                "latn" => "latin".to_owned(),
+                "cyrl" => "cyrillic".to_owned(),
+                script => script.to_owned(),
+            })
+    });
+    if let Some(modifier) = modifier {
+        locale.push('@');
+        locale.push_str(&modifier);
+    }
+    Ok(locale)
+}
+
+/// Whether the language of `lang`, a language tag or desktop entry locale, is written
+/// right-to-left
+pub fn is_rtl_language(lang: &str) -> bool {
+    let primary = lang
+        .split(['-', '_', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    RTL_LANGUAGES.contains(&primary.as_str())
+}
@@ -0,0 +1,606 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use super::apparmor::{is_valid_policy_path, superseded_group};
+use super::reader::{parse_exec, ExecFlags, PackageFiles};
+use super::{
+    normalize_icon, policy_groups, sanitize_svg, AppArmor, DesktopEntry, PolicyError,
+    PolicyTemplate, ReadPackageError, Target, DESKTOP_ENTRY_GROUP,
+};
+use super::{qmlapp, snapshot, userscript};
+
+const REQUIRED_DESKTOP_KEYS: &[&str] = &["Name", "Exec", "Icon", "Type"];
+
+/// Values of the `X-Lomiri-Supported-Orientations` key, compared case-insensitively
+const LOMIRI_ORIENTATIONS: &[&str] = &[
+    "primary",
+    "portrait",
+    "invertedportrait",
+    "landscape",
+    "invertedlandscape",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The package works, but likely not as intended
+    Warning,
+    /// The package is broken or won't install
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintMessage {
+    pub severity: Severity,
+    /// The file inside of the package the message refers to, if any
+    pub file: Option<String>,
+    pub message: String,
+}
+
+impl LintMessage {
+    /// A warning about something left out while building a package
+    pub(super) fn warning(message: impl Into<String>) -> Self {
+        LintMessage {
+            severity: Severity::Warning,
+            file: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LintMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.file {
+            Some(ref file) => write!(f, "{}: {}: {}", severity, file, self.message),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Linter {
+    messages: Vec<LintMessage>,
+}
+
+impl Linter {
+    fn warning(&mut self, file: Option<&str>, message: impl Into<String>) {
+        self.push(Severity::Warning, file, message.into());
+    }
+
+    fn error(&mut self, file: Option<&str>, message: impl Into<String>) {
+        self.push(Severity::Error, file, message.into());
+    }
+
+    fn push(&mut self, severity: Severity, file: Option<&str>, message: String) {
+        self.messages.push(LintMessage {
+            severity,
+            file: file.map(String::from),
+            message,
+        });
+    }
+}
+
+/// Check the click package at `path` for problems. Fails only if the package can't be read at all.
+pub fn lint(path: &Path) -> Result<Vec<LintMessage>, ReadPackageError> {
+    let files = PackageFiles::read(path)?;
+    let manifest = files.manifest()?;
+    let mut linter = Linter::default();
+
+    if !is_valid_package_name(&manifest.name) {
+        linter.error(
+            Some("manifest"),
+            format!(
+                "Package name \"{}\" may only contain lowercase letters, digits, '.', '+' and '-' \
+                 and must start with a letter or digit",
+                manifest.name
+            ),
+        );
+    }
+
+    if !is_valid_version(&manifest.version) {
+        linter.error(
+            Some("manifest"),
+            format!(
+                "Version \"{}\" has to start with a digit and may only contain letters, digits, \
+                 '.', '+', '~' and '-'",
+                manifest.version
+            ),
+        );
+    }
+
+    let target = Target::from_framework(&manifest.framework);
+    if target.is_none() {
+        linter.warning(
+            Some("manifest"),
+            format!(
+                "Unknown framework \"{}\", the package may not install",
+                manifest.framework
+            ),
+        );
+    }
+
+    if manifest.hooks.is_empty() {
+        linter.error(Some("manifest"), "The manifest contains no hooks");
+    }
+
+    if manifest.hooks.len() == 1 {
+        let appname = manifest.hooks.keys().next().unwrap();
+        if !manifest.name.starts_with(&format!("{}.", appname)) {
+            linter.warning(
+                Some("manifest"),
+                format!(
+                    "Hook \"{}\" doesn't match the package name \"{}\"",
+                    appname, manifest.name
+                ),
+            );
+        }
+    }
+
+    for (appname, hook) in &manifest.hooks {
+        if !is_valid_appname(appname) {
+            linter.error(
+                Some("manifest"),
+                format!("Invalid app name \"{}\"", appname),
+            );
+        }
+
+        match files.data.get(&hook.apparmor) {
+            Some(apparmor) => match serde_json::from_slice::<AppArmor>(apparmor) {
+                Ok(apparmor) => lint_apparmor(&mut linter, &hook.apparmor, &apparmor, target),
+                Err(err) => linter.error(
+                    Some(&hook.apparmor),
+                    format!("Invalid AppArmor policy: {}", err),
+                ),
+            },
+            None => linter.error(
+                Some("manifest"),
+                format!("Missing AppArmor policy {}", hook.apparmor),
+            ),
+        }
+
+        match files.data.get(&hook.desktop) {
+            Some(desktop) => match std::str::from_utf8(desktop) {
+                Ok(desktop) => lint_desktop(&mut linter, &files, &hook.desktop, desktop),
+                Err(_) => linter.error(Some(&hook.desktop), "Desktop file is not valid UTF-8"),
+            },
+            None => linter.error(
+                Some("manifest"),
+                format!("Missing desktop file {}", hook.desktop),
+            ),
+        }
+    }
+
+    Ok(linter.messages)
+}
+
+fn lint_apparmor(linter: &mut Linter, file: &str, apparmor: &AppArmor, target: Option<Target>) {
+    if let Err(err) = apparmor.template.parse::<PolicyTemplate>() {
+        linter.error(Some(file), err);
+    }
+
+    for path in apparmor.read_path.iter().chain(&apparmor.write_path) {
+        if !is_valid_policy_path(path) {
+            linter.error(
+                Some(file),
+                PolicyError::InvalidPath(path.clone()).to_string(),
+            );
+        }
+    }
+
+    if let Some(target) = target {
+        if apparmor.policy_version != target.policy_version() {
+            linter.warning(
+                Some(file),
+                format!(
+                    "Policy version {} doesn't match the framework {}, expected {}",
+                    apparmor.policy_version,
+                    target.framework(),
+                    target.policy_version()
+                ),
+            );
+        }
+    }
+
+    let known_groups = match policy_groups(&apparmor.policy_version) {
+        Some(known_groups) => known_groups,
+        None => {
+            linter.warning(
+                Some(file),
+                format!(
+                    "Policy version {} can't be checked, only 16.04 and 20.04 are known",
+                    apparmor.policy_version
+                ),
+            );
+            return;
+        }
+    };
+
+    let mut seen = HashSet::new();
+    for group in &apparmor.policy_groups {
+        if !known_groups.iter().any(|known| known.name == group) {
+            let err = PolicyError::UnknownGroup {
+                group: group.clone(),
+                policy_version: apparmor.policy_version.clone(),
+            };
+            linter.error(Some(file), err.to_string());
+        }
+        if !seen.insert(group) {
+            linter.warning(Some(file), format!("Duplicate policy group \"{}\"", group));
+        }
+    }
+
+    if let Some((group, superseded)) = superseded_group(&apparmor.policy_groups) {
+        let err = PolicyError::SupersededGroup(group.to_owned(), superseded.to_owned());
+        linter.warning(Some(file), err.to_string());
+    }
+}
+
+fn lint_desktop(linter: &mut Linter, files: &PackageFiles, file: &str, content: &str) {
+    let mut group = None;
+    let mut groups = HashSet::new();
+    let mut keys = HashSet::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                linter.error(
+                    Some(file),
+                    format!("Line {}: Invalid group header", line_no),
+                );
+            }
+            if group.is_none() && line != "[Desktop Entry]" {
+                linter.error(Some(file), "The first group must be [Desktop Entry]");
+            }
+            if !groups.insert(line.to_owned()) {
+                linter.error(
+                    Some(file),
+                    format!("Line {}: Duplicate group {}", line_no, line),
+                );
+            }
+            group = Some(line.to_owned());
+            keys.clear();
+            continue;
+        }
+        if group.is_none() {
+            linter.error(
+                Some(file),
+                format!("Line {}: Key outside of a group", line_no),
+            );
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => {
+                linter.error(Some(file), format!("Line {}: Expected a key", line_no));
+                continue;
+            }
+        };
+        if !is_valid_desktop_key(key) {
+            linter.error(
+                Some(file),
+                format!("Line {}: Invalid key \"{}\"", line_no, key),
+            );
+        }
+        if !keys.insert(key.to_owned()) {
+            linter.error(
+                Some(file),
+                format!("Line {}: Duplicate key \"{}\"", line_no, key),
+            );
+        }
+        if value.chars().any(char::is_control) {
+            linter.error(
+                Some(file),
+                format!(
+                    "Line {}: Value of \"{}\" contains control characters",
+                    line_no, key
+                ),
+            );
+        }
+        if let Some(escape) = invalid_escape(value) {
+            linter.error(
+                Some(file),
+                format!(
+                    "Line {}: Invalid escape sequence \"\\{}\" in value of \"{}\"",
+                    line_no, escape, key
+                ),
+            );
+        }
+    }
+
+    let desktop = DesktopEntry::parse(content);
+    let entry = match desktop.group(DESKTOP_ENTRY_GROUP) {
+        Some(entry) => entry,
+        None => {
+            linter.error(Some(file), "Missing [Desktop Entry] group");
+            return;
+        }
+    };
+
+    for key in REQUIRED_DESKTOP_KEYS {
+        if entry.get_raw(key).is_none() {
+            linter.error(Some(file), format!("Missing required key \"{}\"", key));
+        }
+    }
+
+    for icon in ["Icon", "X-Lomiri-Symbolic-Icon", "X-Lomiri-Splash-Image"]
+        .iter()
+        .filter_map(|key| entry.get(key))
+    {
+        lint_icon(linter, files, file, &icon);
+    }
+
+    if let Some(orientations) = entry.get("X-Lomiri-Supported-Orientations") {
+        for orientation in orientations.split(',').map(str::trim) {
+            if !LOMIRI_ORIENTATIONS.contains(&orientation.to_lowercase().as_str()) {
+                linter.error(
+                    Some(file),
+                    format!("Unknown supported orientation \"{}\"", orientation),
+                );
+            }
+        }
+    }
+
+    if let Some(exec) = entry.get("Exec") {
+        let mut exec = lint_exec(linter, files, file, &exec);
+        if matches!(exec, Some(ref exec) if snapshot::is_local_url(&exec.url)) {
+            let prefix = match file.rsplit_once('/') {
+                Some((dir, _)) => format!("{}/", dir),
+                None => String::new(),
+            };
+            if let Err(err) = snapshot::read_snapshot(&files.data, &prefix) {
+                linter.error(Some(file), err);
+            }
+        }
+        if let Some(ref mut exec) = exec {
+            if let Some(main_qml) = exec.qml_app.clone() {
+                match qmlapp::read_qml_config(&files.data, &main_qml) {
+                    Ok(config) => exec.merge_qml_config(config),
+                    Err(err) => linter.error(Some(file), err),
+                }
+            }
+        }
+        if let Some(search_path) = exec.as_ref().and_then(|exec| exec.webapp_model.as_ref()) {
+            if let Err(err) = userscript::read_webapp_model(&files.data, search_path) {
+                linter.error(Some(file), err);
+            }
+        }
+        if let Some(port) = exec.and_then(|exec| exec.container.inspector_port) {
+            linter.warning(
+                Some(file),
+                format!(
+                    "The web inspector is enabled on port {}, only use it for debugging",
+                    port
+                ),
+            );
+        }
+    }
+
+    for id in entry.get_list("Actions") {
+        let group = format!("Desktop Action {}", id);
+        let action = match desktop.group(&group) {
+            Some(action) => action,
+            None => {
+                linter.error(
+                    Some(file),
+                    format!("Action {} has no [{}] group", id, group),
+                );
+                continue;
+            }
+        };
+        if action.get_raw("Name").is_none() {
+            linter.error(Some(file), format!("Action {} has no Name", id));
+        }
+        match action.get("Exec") {
+            Some(exec) => {
+                lint_exec(linter, files, file, &exec);
+            }
+            None => linter.warning(Some(file), format!("Action {} has no Exec key", id)),
+        }
+        if let Some(icon) = action.get("Icon") {
+            lint_icon(linter, files, file, &icon);
+        }
+    }
+}
+
+fn lint_icon(linter: &mut Linter, files: &PackageFiles, file: &str, icon: &str) {
+    match files.data.get(icon) {
+        Some(data) => match normalize_icon(data) {
+            Ok(normalized) => {
+                let removed_content = normalized.extension == "svg"
+                    && sanitize_svg(&String::from_utf8_lossy(data))
+                        .map_or(false, |svg| svg.removed_content);
+                if removed_content {
+                    linter.warning(
+                        Some(file),
+                        format!(
+                            "Icon {} contains elements or attributes not allowed in icons, \
+                             e.g. scripts or external references, they won't be rendered",
+                            icon
+                        ),
+                    );
+                }
+            }
+            Err(err) => linter.error(
+                Some(file),
+                format!("Icon {} is not a supported image: {}", icon, err),
+            ),
+        },
+        None => linter.error(Some(file), format!("Icon {} doesn't exist", icon)),
+    }
+}
+
+/// Check a `webapp-container` command line, returns its flags if it could be parsed
+fn lint_exec(
+    linter: &mut Linter,
+    files: &PackageFiles,
+    file: &str,
+    exec: &str,
+) -> Option<ExecFlags> {
+    match parse_exec(exec) {
+        // Pages of offline snapshots, relative to the package
+        Ok(exec) if snapshot::is_local_url(&exec.url) => {
+            if !files.data.contains_key(&exec.url) {
+                linter.error(Some(file), format!("Missing offline page {}", exec.url));
+            }
+            Some(exec)
+        }
+        Ok(exec) => {
+            let patterns = exec
+                .url_patterns
+                .split(',')
+                .filter(|pat| !pat.is_empty())
+                .collect::<Vec<_>>();
+            if !patterns.is_empty()
+                && !patterns
+                    .iter()
+                    .any(|pat| url_pattern_matches(pat, &exec.url))
+            {
+                linter.warning(
+                    Some(file),
+                    format!(
+                        "The url patterns don't cover the url {}, \
+                         it will open in the browser instead",
+                        exec.url
+                    ),
+                );
+            }
+            Some(exec)
+        }
+        Err(err) => {
+            linter.error(Some(file), format!("Invalid Exec key: {}", err));
+            None
+        }
+    }
+}
+
+/// Click package names, see https://click.readthedocs.io/en/latest/file-format.html
+pub(super) fn is_valid_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name.len() >= 2
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+.-".contains(c))
+}
+
+/// Debian package versions without epoch, as click only accepts those
+pub(super) fn is_valid_version(version: &str) -> bool {
+    let mut chars = version.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_digit())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+.~-".contains(c))
+}
+
+fn is_valid_appname(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+}
+
+/// Desktop entry keys, optionally with a locale, e.g. `Name[de]`
+fn is_valid_desktop_key(key: &str) -> bool {
+    let (name, locale) = match key.split_once('[') {
+        Some((name, locale)) => match locale.strip_suffix(']') {
+            Some(locale) => (name, Some(locale)),
+            None => return false,
+        },
+        None => (key, None),
+    };
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && locale.map_or(true, |locale| {
+            !locale.is_empty()
+                && locale
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_@.-".contains(c))
+        })
+}
+
+/// The first escape sequence in `value` not allowed by the desktop entry spec, if any
+fn invalid_escape(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('s') | Some('n') | Some('t') | Some('r') | Some('\\') | Some(';') => {}
+                Some(other) => return Some(other),
+                None => return Some(' '),
+            }
+        }
+    }
+    None
+}
+
+/// Match `url` against a webapp-container url pattern, where `*` matches any sequence of
+/// characters and `?` makes the preceding character optional (e.g. `https?://*.example.com/*`)
+pub fn url_pattern_matches(pattern: &str, url: &str) -> bool {
+    let url = url.chars().collect::<Vec<_>>();
+    // `reached[i]` tells whether the pattern read so far matches the first `i` characters of
+    // the url, which keeps the matching in O(pattern length * url length)
+    let mut reached = vec![false; url.len() + 1];
+    reached[0] = true;
+
+    let mut pattern = pattern.chars().peekable();
+    while let Some(c) = pattern.next() {
+        let optional = c != '*' && pattern.next_if_eq(&'?').is_some();
+        let mut next = vec![false; url.len() + 1];
+        for i in 0..=url.len() {
+            next[i] = match c {
+                '*' => reached[i] || (i > 0 && next[i - 1]),
+                _ => (optional && reached[i]) || (i > 0 && reached[i - 1] && url[i - 1] == c),
+            };
+        }
+        if !next.contains(&true) {
+            return false;
+        }
+        reached = next;
+    }
+    reached[url.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::url_pattern_matches;
+
+    #[test]
+    fn matches_url_patterns() {
+        assert!(url_pattern_matches(
+            "https?://example.com/*",
+            "http://example.com/"
+        ));
+        assert!(url_pattern_matches(
+            "https?://example.com/*",
+            "https://example.com/a/b"
+        ));
+        assert!(url_pattern_matches(
+            "https?://*.example.com/*",
+            "https://www.example.com/"
+        ));
+        assert!(url_pattern_matches("*", ""));
+        assert!(!url_pattern_matches(
+            "https?://example.com/*",
+            "httpss://example.com/"
+        ));
+        assert!(!url_pattern_matches(
+            "https?://*.example.com/*",
+            "https://example.com/"
+        ));
+        assert!(!url_pattern_matches(
+            "https://example.com",
+            "https://example.com/"
+        ));
+    }
+
+    #[test]
+    fn matches_pathological_patterns_quickly() {
+        let url = format!("https://{}", "a".repeat(10_000));
+        assert!(!url_pattern_matches("https://*a*a*a*a*a*a*b", &url));
+        assert!(url_pattern_matches("https://*a*a*a*a*a*a*", &url));
+        assert!(!url_pattern_matches(&"a?".repeat(100), &"a".repeat(101)));
+    }
+}
@@ -0,0 +1,1147 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::linux::fs::MetadataExt as _;
+use std::os::unix::fs::{MetadataExt as _, PermissionsExt as _};
+use std::path::{Path, PathBuf};
+
+use ar_archive_writer::NewArchiveMember;
+use reqwest::blocking as reqwest;
+
+use flate2::write::GzEncoder;
+use flate2::{Compression, GzBuilder};
+
+use blake2::digest::{Update, VariableOutput};
+use blake2::VarBlake2b;
+
+use md5::{Digest, Md5};
+
+use csscolorparser::Color;
+
+use deunicode::deunicode;
+
+use crate::pwa::{DisplayMode, Orientation, ShortcutItem};
+use crate::resolvable::Resolved;
+
+mod apparmor;
+mod bundle;
+mod container;
+mod desktop;
+mod freedesktop;
+mod history;
+mod icon;
+mod l10n;
+mod lint;
+mod publish;
+mod qmlapp;
+mod reader;
+mod sanitize;
+mod snapshot;
+mod splash;
+mod target;
+mod userscript;
+
+pub use self::apparmor::*;
+pub use self::bundle::*;
+pub use self::container::*;
+pub use self::desktop::*;
+pub use self::freedesktop::*;
+pub use self::history::*;
+pub use self::icon::*;
+pub use self::l10n::*;
+pub use self::lint::*;
+pub use self::publish::*;
+pub use self::qmlapp::*;
+pub use self::reader::*;
+pub use self::snapshot::*;
+pub use self::splash::*;
+pub use self::target::*;
+pub use self::userscript::*;
+
+/// The version of newly created shortcuts
+pub const INITIAL_VERSION: &str = "1.0.0";
+
+#[derive(Debug)]
+pub struct Package {
+    pub url: String,
+    pub name: String,
+    /// Translations of `name`
+    pub names: Vec<LocalizedName>,
+    pub theme_color: String,
+    /// The color maskable icons are composited on, defaults to the theme color
+    pub background_color: String,
+    pub icon: Icon,
+    /// Whether `icon` is a maskable icon whose padding outside of the safe zone can be cropped
+    pub maskable_icon: bool,
+    /// A monochrome icon, tinted with the theme color and used as the symbolic icon
+    pub monochrome_icon: Option<Icon>,
+    pub splash: Splash,
+    /// The orientations the app may be shown in, unrestricted if `None`
+    pub orientation: Option<Orientation>,
+    /// Additional entry points, shown in the app's context menu
+    pub actions: Vec<Action>,
+    pub url_patterns: String,
+    /// AppArmor policy groups requested in addition to `networking` and `webview`
+    pub permissions: Vec<String>,
+    pub policy_template: PolicyTemplate,
+    /// Paths the app may read in addition to the access of its policy groups
+    pub read_paths: Vec<String>,
+    /// Paths the app may write in addition to the access of its policy groups
+    pub write_paths: Vec<String>,
+    pub enable_address_bar: bool,
+    pub enable_back_forward: bool,
+    pub enable_fullscreen: bool,
+    pub user_agent: String,
+    /// Options of the container the app runs in
+    pub container: ContainerOptions,
+    /// Scripts and stylesheets injected into the site, e.g. built-in `TWEAKS`
+    pub user_scripts: Vec<UserScript>,
+    /// Ship a snapshot of the site's static files and open it instead of the site
+    pub offline_snapshot: Option<OfflineSnapshot>,
+    /// The Ubuntu Touch release the package is built for
+    pub target: Target,
+    /// The package version. Rebuilds of a shortcut need a higher version to install as upgrades.
+    pub version: String,
+    /// Create byte-identical packages for identical inputs by normalizing all archive metadata
+    pub reproducible: bool,
+    /// The store metadata of a published package, `None` for an anonymous shortcut
+    pub publishing: Option<Publishing>,
+}
+
+impl Default for Package {
+    /// A package without a url or name, using the default icon and the default settings
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            name: String::new(),
+            names: Vec::new(),
+            theme_color: String::new(),
+            background_color: String::new(),
+            icon: Icon::Local(String::new()),
+            maskable_icon: false,
+            monochrome_icon: None,
+            splash: Splash::default(),
+            orientation: None,
+            actions: Vec::new(),
+            url_patterns: String::new(),
+            permissions: Vec::new(),
+            policy_template: PolicyTemplate::default(),
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            enable_address_bar: false,
+            enable_back_forward: false,
+            enable_fullscreen: false,
+            user_agent: String::new(),
+            container: ContainerOptions::default(),
+            user_scripts: Vec::new(),
+            offline_snapshot: None,
+            target: Target::default(),
+            version: INITIAL_VERSION.to_owned(),
+            reproducible: false,
+            publishing: None,
+        }
+    }
+}
+
+impl Package {
+    /// Build the click package, returning its path and warnings about what was left out of it
+    pub fn create(&self) -> Result<(PathBuf, Vec<LintMessage>), Box<dyn std::error::Error>> {
+        self.validate()?;
+        let appname = self.appname();
+        let package_name = format!("{}.{}", appname, namespace(self.publishing.as_ref()));
+        let mut warnings = Vec::new();
+        let path = create_click(
+            &appname,
+            &self.name,
+            &self.version,
+            self.target,
+            self.publishing.as_ref(),
+            self.reproducible,
+            |data| {
+                let mut hooks = BTreeMap::new();
+                let hook = self.write_app(data, &package_name, None, &mut warnings)?;
+                hooks.insert(appname.clone(), hook);
+                Ok(hooks)
+            },
+        )?;
+        Ok((path, warnings))
+    }
+
+    /// Write the AppArmor policy, icons and desktop entry of the app into the data directory
+    /// `data`, or into its subdirectory `subdir` if given, and return the hook referencing them.
+    /// `package_name` is the full name of the click package containing the app. Warnings are
+    /// added to `warnings`, referring to `subdir` if given.
+    fn write_app(
+        &self,
+        data: &Path,
+        package_name: &str,
+        subdir: Option<&str>,
+        warnings: &mut Vec<LintMessage>,
+    ) -> Result<ManifestHook, Box<dyn std::error::Error>> {
+        let mut app_warnings = Vec::new();
+        let hook = self.write_app_files(data, package_name, subdir, &mut app_warnings);
+        warnings.extend(app_warnings.into_iter().map(|warning| LintMessage {
+            file: subdir.map(str::to_owned),
+            ..warning
+        }));
+        hook
+    }
+
+    fn write_app_files(
+        &self,
+        data: &Path,
+        package_name: &str,
+        subdir: Option<&str>,
+        warnings: &mut Vec<LintMessage>,
+    ) -> Result<ManifestHook, Box<dyn std::error::Error>> {
+        let (dir, prefix) = match subdir {
+            Some(subdir) => {
+                let dir = data.join(subdir);
+                mkdir(&dir)?;
+                (dir, format!("{}/", subdir))
+            }
+            None => (data.to_owned(), String::new()),
+        };
+
+        let apparmor = self.policy()?;
+
+        write_file(
+            &dir.join(Path::new("shortcut.apparmor")),
+            &apparmor.to_string()?,
+        )?;
+
+        let icons = self.embed_icons(&dir, warnings)?.prefixed(&prefix);
+
+        let (webapp_model, user_scripts) = if self.user_scripts.is_empty() {
+            (None, Vec::new())
+        } else {
+            let user_scripts = write_webapp_model(self, &dir)?;
+            (
+                Some(format!("{}{}", prefix, WEBAPP_MODEL_DIR)),
+                user_scripts,
+            )
+        };
+
+        let snapshot = match self.offline_snapshot {
+            Some(ref options) => {
+                let snapshot = SiteSnapshot::crawl(&self.url, options)?;
+                snapshot.write(&dir, options)?;
+                warnings.extend(snapshot.warnings.iter().map(LintMessage::warning));
+                Some(snapshot)
+            }
+            None => None,
+        };
+
+        if self.container.runtime == Runtime::Qml {
+            write_qml_app(
+                self,
+                &dir,
+                package_name,
+                &prefix,
+                webapp_model.as_deref(),
+                &user_scripts,
+            )?;
+        }
+
+        write_file(
+            &dir.join(Path::new("shortcut.desktop")),
+            &data_desktop_entry(
+                self,
+                &icons,
+                &prefix,
+                webapp_model.as_deref(),
+                snapshot.as_ref(),
+            )
+            .to_string(),
+        )?;
+
+        Ok(ManifestHook {
+            apparmor: format!("{}shortcut.apparmor", prefix),
+            desktop: format!("{}shortcut.desktop", prefix),
+        })
+    }
+
+    /// The AppArmor policy of the app. The QML runtime isn't a webapp, so it needs the SDK
+    /// template instead of the webapp template.
+    fn policy(&self) -> Result<AppArmor, PolicyError> {
+        let template = match (self.container.runtime, self.policy_template) {
+            (Runtime::Qml, PolicyTemplate::UbuntuWebapp) => PolicyTemplate::UbuntuSdk,
+            (_, template) => template,
+        };
+        PolicyBuilder::new(self.target)
+            .template(template)
+            .groups(&self.permissions)
+            .read_paths(&self.read_paths)
+            .write_paths(&self.write_paths)
+            .build()
+    }
+
+    /// Download or copy the icon (or the default icon) into `dir` and return its file name
+    fn embed_icon(
+        &self,
+        dir: &Path,
+        warnings: &mut Vec<LintMessage>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        // Icons that can't be decoded are replaced by the default icon
+        let icon = match self.icon.load()? {
+            Some(data) if self.maskable_icon => {
+                Some(normalize_maskable_icon(&data, &self.background_color()))
+            }
+            Some(data) => Some(normalize_icon(&data)),
+            None => None,
+        };
+        let icon = match icon {
+            Some(Ok(icon)) => Some(icon),
+            Some(Err(err)) => {
+                warnings.push(LintMessage::warning(format!(
+                    "Using the default icon: {}",
+                    err
+                )));
+                None
+            }
+            None => None,
+        };
+
+        let icon_filename = match icon {
+            Some(icon) => {
+                let icon_fname = icon.filename();
+                fs::write(dir.join(&icon_fname), &icon.data)?;
+                icon_fname
+            }
+            None => {
+                let icon_fname = "icon.svg".to_owned();
+                write_icon(&dir.join(Path::new(&icon_fname)))?;
+                icon_fname
+            }
+        };
+
+        Ok(icon_filename)
+    }
+
+    fn embed_icons(
+        &self,
+        dir: &Path,
+        warnings: &mut Vec<LintMessage>,
+    ) -> Result<EmbeddedIcons, Box<dyn std::error::Error>> {
+        let theme_color = parse_color(&self.theme_color);
+        Ok(EmbeddedIcons {
+            icon: self.embed_icon(dir, warnings)?,
+            symbolic_icon: embed_extra_icon(
+                &self.monochrome_icon,
+                dir,
+                "icon-symbolic",
+                |data| tint_monochrome_icon(data, &theme_color),
+                warnings,
+            )?,
+            splash_image: embed_extra_icon(
+                &self.splash.image,
+                dir,
+                "splash",
+                normalize_icon,
+                warnings,
+            )?,
+            actions: self
+                .actions
+                .iter()
+                .enumerate()
+                .map(|(idx, action)| {
+                    embed_extra_icon(&action.icon, dir, &action_id(idx), normalize_icon, warnings)
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn background_color(&self) -> Color {
+        if self.background_color.is_empty() {
+            parse_color(&self.theme_color)
+        } else {
+            parse_color(&self.background_color)
+        }
+    }
+
+    fn appname(&self) -> String {
+        let url = url::Url::parse(&self.url).ok();
+
+        // Use the url host and a short hash of the path to generate a reasonably unique appname.
+        // We use a short hash for the url path to ensure we don't exceed the name limits of unix
+        // domain sockets (108 chars as stated here http://man7.org/linux/man-pages/man7/unix.7.html
+        // for unix domain sockets). Note that the socket will be created by the webapp container
+        // and the name will be of the format
+        // "/home/phablet/.local/share/<host-part>-<path-hash>.<namespace>/SingletonSocket"
+        // Also note that we rely on the webapp container to use the short name format (see above).
+        // The long format is
+        // "/home/phablet/.local/share/<host-part>-<path-hash>.<namespace>/<host-part>-<path-hash>/SingletonSocket".
+        // UNIX_SOCKET_MAX_LEN is 107 characters because the string will become null-terminated.
+        const UNIX_SOCKET_MAX_LEN: usize = 107;
+        const SHORT_HASH_LEN: usize = 16;
+        // 41 chars left for the encoded host name with the default namespace
+        let available_len = (UNIX_SOCKET_MAX_LEN
+            - "/home/phablet/.local/share/-./SingletonSocket".len()
+            - SHORT_HASH_LEN)
+            .saturating_sub(namespace(self.publishing.as_ref()).len());
+
+        let (url_host_part, url_path_part, url_port) = url
+            .map(|url| {
+                (
+                    url.host_str().map(String::from).unwrap_or_default(),
+                    url.path().to_owned(),
+                    url.port(),
+                )
+            })
+            .unwrap_or_default();
+
+        // If the url contains a port number, include it in the main appname part
+        let main_part = match url_port {
+            Some(port) => format!("{}-{}", url_host_part, port),
+            None => url_host_part,
+        };
+
+        // Generate the short hash for the url path part
+        let url_path_hash =
+            if url_path_part != "/" && !url_path_part.is_empty() || url_port.is_some() {
+                // SHORT_HASH_LEN / 2 because we need (at most) two hex digits to encode a byte
+                let mut short_hash =
+                    VarBlake2b::new(SHORT_HASH_LEN / 2).expect("Failed to create blake2 hasher");
+                short_hash.update(url_path_part);
+                // If the url contains a port number, also include it in the hash.
+                // This is needed to support the edge case where the main part is too long and gets trimmed such that
+                // the port number is no longer included.
+                if let Some(port) = url_port {
+                    short_hash.update(port.to_le_bytes())
+                }
+                hex::encode(short_hash.finalize_boxed())
+            } else {
+                String::new()
+            };
+
+        // Remove forbidden characters
+        let ascii = main_part.to_ascii_lowercase();
+        let allowed_chars = ascii
+            .chars()
+            .filter_map(|c| {
+                if c == '/' || c == '.' || c == '_' || c == '-' {
+                    Some('-')
+                } else if ('a'..='z').contains(&c) || c.is_digit(10) {
+                    Some(c)
+                } else {
+                    None
+                }
+            })
+            .collect::<String>();
+
+        // Cut final string to allowed available len for host part
+        let ascii_bytes = allowed_chars
+            .into_bytes()
+            .into_iter()
+            .take(available_len)
+            .collect::<Vec<_>>();
+
+        // Note that this should always succeed since we ensure the string only contains a
+        // restricted set of ASCII characters at this point.
+        let final_host_part =
+            String::from_utf8(ascii_bytes).expect("Failed to convert ascii bytes back to utf-8");
+
+        if url_path_hash.is_empty() {
+            final_host_part
+        } else {
+            format!("{}-{}", final_host_part, url_path_hash)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Icon {
+    Local(String),
+    Remote(String),
+}
+
+/// A desktop entry action launching the web app at another url, e.g. one of the manifest's
+/// shortcuts
+#[derive(Debug)]
+pub struct Action {
+    pub name: String,
+    pub url: String,
+    pub icon: Option<Icon>,
+}
+
+impl From<ShortcutItem<Resolved>> for Action {
+    fn from(shortcut: ShortcutItem<Resolved>) -> Self {
+        // Manifests usually list icons from small to large
+        let icon = shortcut
+            .icons
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .find(|icon| icon.purpose.any)
+            .map(|icon| Icon::Remote(icon.src.to_string()));
+        Action {
+            name: shortcut.name,
+            url: shortcut.url.to_string(),
+            icon,
+        }
+    }
+}
+
+/// The identifier of the action at `index` in the desktop entry
+fn action_id(index: usize) -> String {
+    format!("shortcut-{}", index + 1)
+}
+
+impl Icon {
+    /// Download or read the icon, empty paths and urls yield `None`
+    fn load(&self) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match self {
+            Icon::Remote(icon_url) if !icon_url.is_empty() => Ok(Some(download(icon_url)?)),
+            Icon::Local(icon_path) if !icon_path.is_empty() => Ok(Some(fs::read(icon_path)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ControlManifest {
+    architecture: String,
+    description: String,
+    framework: String,
+    hooks: BTreeMap<String, ManifestHook>,
+    maintainer: String,
+    name: String,
+    title: String,
+    version: String,
+    #[serde(rename = "installed-size")]
+    #[serde(default)]
+    installed_size: String,
+    #[serde(rename = "x-license")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+}
+
+impl ControlManifest {
+    fn new(
+        appname: String,
+        title: String,
+        version: String,
+        installed_size: u64,
+        target: Target,
+        publishing: Option<&Publishing>,
+        hooks: BTreeMap<String, ManifestHook>,
+    ) -> Self {
+        Self {
+            architecture: "all".to_owned(),
+            description: description(publishing).to_owned(),
+            framework: target.framework().to_owned(),
+            hooks,
+            maintainer: maintainer(publishing).to_owned(),
+            name: format!("{}.{}", appname, namespace(publishing)),
+            title,
+            version,
+            installed_size: installed_size.to_string(),
+            license: publishing.map(|publishing| publishing.license.clone()),
+        }
+    }
+
+    fn to_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ManifestHook {
+    apparmor: String,
+    desktop: String,
+}
+
+fn download(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let resp = reqwest::get(url)?;
+    Ok(resp.bytes()?.to_vec())
+}
+
+/// The webapp-container UI matching a display mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayFlags {
+    pub enable_address_bar: bool,
+    pub enable_back_forward: bool,
+    pub enable_fullscreen: bool,
+}
+
+impl From<DisplayMode> for DisplayFlags {
+    fn from(display: DisplayMode) -> Self {
+        match display {
+            DisplayMode::Fullscreen => DisplayFlags {
+                enable_fullscreen: true,
+                ..Default::default()
+            },
+            DisplayMode::Standalone => DisplayFlags::default(),
+            DisplayMode::MinimalUI => DisplayFlags {
+                enable_back_forward: true,
+                ..Default::default()
+            },
+            DisplayMode::Browser => DisplayFlags {
+                enable_address_bar: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// The value of the `X-Lomiri-Supported-Orientations` key for a manifest orientation
+fn supported_orientations(orientation: Orientation) -> &'static str {
+    match orientation {
+        Orientation::Any => "portrait,invertedPortrait,landscape,invertedLandscape",
+        Orientation::Natural => "primary",
+        Orientation::Landscape => "landscape,invertedLandscape",
+        Orientation::Portrait => "portrait,invertedPortrait",
+        Orientation::PortraitPrimary => "portrait",
+        Orientation::PortraitSecondary => "invertedPortrait",
+        Orientation::LandscapePrimary => "landscape",
+        Orientation::LandscapeSecondary => "invertedLandscape",
+    }
+}
+
+/// The file names of the icons embedded into the data directory
+struct EmbeddedIcons {
+    icon: String,
+    symbolic_icon: Option<String>,
+    splash_image: Option<String>,
+    /// The icons of `Package::actions`, in the same order
+    actions: Vec<Option<String>>,
+}
+
+impl EmbeddedIcons {
+    /// Prepend `prefix` to the file names, for icons embedded into a subdirectory
+    fn prefixed(self, prefix: &str) -> Self {
+        let prefix_name = |name: String| format!("{}{}", prefix, name);
+        Self {
+            icon: prefix_name(self.icon),
+            symbolic_icon: self.symbolic_icon.map(prefix_name),
+            splash_image: self.splash_image.map(prefix_name),
+            actions: self
+                .actions
+                .into_iter()
+                .map(|icon| icon.map(prefix_name))
+                .collect(),
+        }
+    }
+}
+
+/// Convert the optional `icon` with `normalize` and write it into `dir` as `name`, returning its
+/// file name. Icons that fail to convert are skipped with a warning added to `warnings`.
+fn embed_extra_icon(
+    icon: &Option<Icon>,
+    dir: &Path,
+    name: &str,
+    normalize: impl Fn(&[u8]) -> Result<NormalizedIcon, String>,
+    warnings: &mut Vec<LintMessage>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let data = match icon {
+        Some(icon) => icon.load()?,
+        None => None,
+    };
+    let data = match data {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+
+    match normalize(&data) {
+        Ok(icon) => {
+            let icon_fname = format!("{}.{}", name, icon.extension);
+            fs::write(dir.join(&icon_fname), &icon.data)?;
+            Ok(Some(icon_fname))
+        }
+        Err(err) => {
+            warnings.push(LintMessage::warning(format!(
+                "Leaving out the {} icon: {}",
+                name, err
+            )));
+            Ok(None)
+        }
+    }
+}
+
+/// Parse a CSS color, falling back to white
+fn parse_color(color: &str) -> Color {
+    color
+        .parse()
+        .unwrap_or_else(|_| Color::from_rgb_u8(255, 255, 255))
+}
+
+/// Build a click package in the cache directory. `write_hooks` writes the apps into the data
+/// directory and returns their hooks, the package is named after `title`.
+fn create_click(
+    appname: &str,
+    title: &str,
+    version: &str,
+    target: Target,
+    publishing: Option<&Publishing>,
+    reproducible: bool,
+    write_hooks: impl FnOnce(&Path) -> Result<BTreeMap<String, ManifestHook>, Box<dyn Error>>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(publishing) = publishing {
+        publishing.validate(appname, version)?;
+    }
+
+    let path = xdg::BaseDirectories::new()?
+        .get_cache_home()
+        .join("webber.timsueberkrueb/click-build");
+    fs::create_dir_all(&path)?;
+    // Clean up
+    fs::remove_dir_all(&path)?;
+    fs::create_dir(&path)?;
+
+    let control = path.join(Path::new("control"));
+    let data = path.join(Path::new("data"));
+
+    mkdir(&control)?;
+    mkdir(&data)?;
+
+    let click_binary = path.join(Path::new("click_binary"));
+    let debian_binary = path.join(Path::new("debian-binary"));
+
+    write_file(&click_binary, "0.4\n")?;
+    write_file(&debian_binary, "2.0\n")?;
+
+    let hooks = write_hooks(&data)?;
+
+    // The control metadata describes the final contents of the data directory,
+    // hence it has to be written after all data files are in place.
+    let data_files = list_files(&data)?;
+    let installed_size = installed_size(&data_files)?;
+
+    write_file(
+        &control.join(Path::new("control")),
+        &control_control_content(appname, version, installed_size, publishing),
+    )?;
+
+    let control_manifest = ControlManifest::new(
+        appname.to_owned(),
+        title.to_owned(),
+        version.to_owned(),
+        installed_size,
+        target,
+        publishing,
+        hooks,
+    );
+
+    write_file(
+        &control.join(Path::new("manifest")),
+        &control_manifest.to_string()?,
+    )?;
+    write_file(
+        &control.join(Path::new("md5sums")),
+        &control_md5sums_content(&data, &data_files)?,
+    )?;
+    write_file(
+        &control.join(Path::new("preinst")),
+        control_preinst_content(),
+    )?;
+    set_mode(&control.join(Path::new("preinst")), 0o755)?;
+
+    let control_tar_gz = path.join(Path::new("control.tar.gz"));
+    let data_tar_gz = path.join(Path::new("data.tar.gz"));
+
+    let fixed_mtime = if reproducible {
+        Some(source_date_epoch())
+    } else {
+        None
+    };
+
+    create_tar_gz(&control_tar_gz, &control, fixed_mtime)?;
+    create_tar_gz(&data_tar_gz, &data, fixed_mtime)?;
+
+    let click_path = path.join(Path::new(&format!("{}.click", package_file_name(title))));
+
+    create_ar(
+        &click_path,
+        &[
+            (&debian_binary, "debian-binary"),
+            (&control_tar_gz, "control.tar.gz"),
+            (&data_tar_gz, "data.tar.gz"),
+            (&click_binary, "_click-binary"),
+        ],
+        fixed_mtime,
+    )?;
+
+    Ok(click_path)
+}
+
+/// The file name of the click package for a package titled `name`
+fn package_file_name(name: &str) -> String {
+    let stripped_name = deunicode(name)
+        .chars()
+        .filter(|c| {
+            ('a'..='z').contains(c)
+                || ('A'..='Z').contains(c)
+                || c.is_digit(10)
+                || *c == ' '
+                || *c == '-'
+                || *c == '.'
+        })
+        .collect::<String>();
+    if stripped_name.is_empty() {
+        "Webapp".to_owned()
+    } else {
+        stripped_name
+    }
+}
+
+/// The timestamp used for reproducible builds, see https://reproducible-builds.org/specs/source-date-epoch/
+fn source_date_epoch() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Create an ar archive from `files`. If `fixed_mtime` is given, the member metadata is
+/// normalized (fixed mtime, root:root ownership, mode 644) instead of copied from the files.
+fn create_ar(filepath: &Path, files: &[(&Path, &str)], fixed_mtime: Option<u64>) -> io::Result<()> {
+    let mut file = fs::File::create(filepath)?;
+    let mut new_members = Vec::new();
+    for (src, target) in files {
+        let mut file = fs::File::open(src)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        let metadata = file.metadata()?;
+        let (mtime, uid, gid, perms) = match fixed_mtime {
+            Some(mtime) => (mtime, 0, 0, 0o100644),
+            None => (
+                metadata.st_mtime() as u64,
+                metadata.st_uid(),
+                metadata.st_gid(),
+                metadata.mode(),
+            ),
+        };
+        new_members.push(NewArchiveMember {
+            buf: Box::new(contents),
+            get_symbols: |_, _| Ok(false),
+            member_name: target.to_string(),
+            mtime,
+            uid,
+            gid,
+            perms,
+        });
+    }
+    ar_archive_writer::write_archive_to_stream(
+        &mut file,
+        &new_members,
+        false,
+        ar_archive_writer::ArchiveKind::Gnu,
+        true,
+        false,
+    )?;
+    Ok(())
+}
+
+/// Create a gzipped tar archive of `dir`. If `fixed_mtime` is given, entries are added in sorted
+/// order with normalized metadata (fixed mtime, root:root ownership, mode 755 for directories and
+/// executables, 644 otherwise) and the gzip header carries the same fixed timestamp.
+fn create_tar_gz(filepath: &Path, dir: &Path, fixed_mtime: Option<u64>) -> io::Result<()> {
+    let archive = std::fs::File::create(filepath)?;
+    let enc = match fixed_mtime {
+        Some(mtime) => {
+            let mtime = u32::try_from(mtime).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("The timestamp {} doesn't fit into a gzip header", mtime),
+                )
+            })?;
+            GzBuilder::new()
+                .mtime(mtime)
+                .write(archive, Compression::default())
+        }
+        None => GzEncoder::new(archive, Compression::default()),
+    };
+    let mut tar = tar::Builder::new(enc);
+    match fixed_mtime {
+        Some(mtime) => {
+            let root = Path::new("./.");
+            append_normalized(&mut tar, root, dir, mtime)?;
+            for entry in list_entries(dir)? {
+                let rel_path = entry.strip_prefix(dir).expect("Entry outside of directory");
+                append_normalized(&mut tar, &root.join(rel_path), &entry, mtime)?;
+            }
+        }
+        None => tar.append_dir_all("./.", dir)?,
+    }
+    tar.finish()?;
+    Ok(())
+}
+
+fn append_normalized<W: Write>(
+    tar: &mut tar::Builder<W>,
+    path: &Path,
+    src: &Path,
+    mtime: u64,
+) -> io::Result<()> {
+    let metadata = fs::metadata(src)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_mtime(mtime);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_username("root")?;
+    header.set_groupname("root")?;
+    if metadata.is_dir() {
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        tar.append_data(&mut header, path, io::empty())
+    } else {
+        let executable = metadata.mode() & 0o111 != 0;
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_mode(if executable { 0o755 } else { 0o644 });
+        header.set_size(metadata.len());
+        tar.append_data(&mut header, path, fs::File::open(src)?)
+    }
+}
+
+/// Recursively list all files and directories in `dir`, sorted by path
+fn list_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            entries.push(path.clone());
+            entries.append(&mut list_entries(&path)?);
+        } else {
+            entries.push(path);
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// Recursively list all regular files in `dir`, sorted by path
+fn list_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(list_entries(dir)?
+        .into_iter()
+        .filter(|path| !path.is_dir())
+        .collect())
+}
+
+/// The installed size in KiB, rounded up, like `du -k -s --apparent-size` computes it
+fn installed_size(files: &[PathBuf]) -> io::Result<u64> {
+    let mut bytes = 0;
+    for file in files {
+        bytes += fs::metadata(file)?.len();
+    }
+    Ok((bytes + 1023) / 1024)
+}
+
+fn set_mode(filename: &Path, mode: u32) -> io::Result<()> {
+    fs::set_permissions(filename, fs::Permissions::from_mode(mode))
+}
+
+fn mkdir(dirname: &Path) -> io::Result<()> {
+    fs::create_dir(dirname)
+}
+
+fn write_file(filename: &Path, content: &str) -> io::Result<()> {
+    let mut file = fs::File::create(filename)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+fn control_control_content(
+    appname: &str,
+    version: &str,
+    installed_size: u64,
+    publishing: Option<&Publishing>,
+) -> String {
+    format!(
+        r#"Package: {}.{}
+Version: {}
+Click-Version: 0.4
+Architecture: all
+Maintainer: {}
+Installed-Size: {}
+Description: {}
+"#,
+        appname,
+        namespace(publishing),
+        version,
+        maintainer(publishing),
+        installed_size,
+        description(publishing),
+    )
+}
+
+/// One line per data file in the format of `md5sum`, with paths relative to the data directory
+fn control_md5sums_content(data: &Path, files: &[PathBuf]) -> io::Result<String> {
+    let mut content = String::new();
+    for file in files {
+        let md5 = Md5::digest(&fs::read(file)?);
+        let rel_path = file
+            .strip_prefix(data)
+            .expect("Data file outside of data directory");
+        content.push_str(&format!("{}  {}\n", hex::encode(md5), rel_path.display()));
+    }
+    Ok(content)
+}
+
+fn control_preinst_content() -> &'static str {
+    r#"#! /bin/sh
+echo "Click packages may not be installed directly using dpkg."
+echo "Use 'click install' instead."
+exit 1"#
+}
+
+/// The command line opening `url` with the settings of `package` in the app in the directory
+/// `prefix`, loading the user scripts from the webapp model in `webapp_model`. Urls saved in the
+/// offline `snapshot` are opened from the package.
+fn data_desktop_exec(
+    package: &Package,
+    url: &str,
+    prefix: &str,
+    webapp_model: Option<&str>,
+    snapshot: Option<&SiteSnapshot>,
+) -> Vec<String> {
+    let local_path = snapshot.and_then(|snapshot| snapshot.local_path(url));
+    let url = match local_path {
+        Some(path) => format!("{}{}/{}", prefix, SNAPSHOT_DIR, path),
+        None => url.to_owned(),
+    };
+    if package.container.runtime == Runtime::Qml {
+        // The QML app reads all other settings from its config
+        return qml_exec(&main_qml_path(prefix), &url);
+    }
+
+    // Links between the pages of the snapshot need to stay within the container
+    let mut url_patterns = package.url_patterns.clone();
+    if local_path.is_some() && !url_patterns.is_empty() {
+        url_patterns.push(',');
+        url_patterns.push_str(&snapshot_url_pattern(prefix));
+    }
+    let mut args = vec![
+        "webapp-container".to_owned(),
+        format!("--webappUrlPatterns={}", url_patterns),
+    ];
+    if let Some(search_path) = webapp_model {
+        args.extend(webapp_args(package, search_path));
+    }
+    args.extend(package.container.args(package.target));
+    if package.enable_address_bar {
+        args.push("--enable-addressbar".to_owned());
+    }
+    if package.enable_back_forward {
+        args.push("--enable-back-forward".to_owned());
+    }
+    if package.enable_fullscreen {
+        args.push("--fullscreen".to_owned());
+    }
+    if !package.user_agent.is_empty() {
+        args.push(format!("--user-agent-string={}", package.user_agent));
+    }
+    // Files left out of the snapshot are still loaded from the site while online
+    if local_path.is_some() && !package.container.local_content_remote_access {
+        args.push("--local-content-can-access-remote-urls".to_owned());
+    }
+    args.push(url);
+    args
+}
+
+fn data_desktop_entry(
+    package: &Package,
+    icons: &EmbeddedIcons,
+    prefix: &str,
+    webapp_model: Option<&str>,
+    snapshot: Option<&SiteSnapshot>,
+) -> DesktopEntry {
+    let mut entry = DesktopEntry::default();
+
+    let group = entry.group_mut(DESKTOP_ENTRY_GROUP);
+    group.set("Name", &package.name);
+    for name in &package.names {
+        group.set_localized("Name", &name.locale, &name.value());
+    }
+    group
+        .set_exec(
+            "Exec",
+            &data_desktop_exec(package, &package.url, prefix, webapp_model, snapshot),
+        )
+        .set("Icon", &icons.icon)
+        .set("Terminal", "false")
+        .set("Type", "Application")
+        .set("X-Lomiri-Touch", "true")
+        .set("X-Webber-Theme-Color", &package.theme_color);
+    if let Some(ref symbolic_icon) = icons.symbolic_icon {
+        group.set("X-Lomiri-Symbolic-Icon", symbolic_icon);
+    }
+    if let Some(orientation) = package.orientation {
+        group.set(
+            "X-Lomiri-Supported-Orientations",
+            supported_orientations(orientation),
+        );
+    }
+    for (key, value) in package.splash_keys(icons.splash_image.as_deref()) {
+        group.set(key, &value);
+    }
+
+    if !package.actions.is_empty() {
+        let ids = (0..package.actions.len())
+            .map(action_id)
+            .collect::<Vec<_>>();
+        group.set_list("Actions", &ids);
+    }
+    for (idx, action) in package.actions.iter().enumerate() {
+        let group = entry.group_mut(&format!("Desktop Action {}", action_id(idx)));
+        group.set("Name", &action.name).set_exec(
+            "Exec",
+            &data_desktop_exec(package, &action.url, prefix, webapp_model, snapshot),
+        );
+        if let Some(ref icon) = icons.actions[idx] {
+            group.set("Icon", icon);
+        }
+    }
+
+    entry
+}
+
+fn write_icon(path: &Path) -> io::Result<()> {
+    let bytes = include_bytes!("../../../assets/logo.svg");
+    let mut file = fs::File::create(path)?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::{create_ar, create_tar_gz};
+
+    /// Write the files of a package to `dir`, replacing earlier contents
+    fn write_files(dir: &Path) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir.join("www/css")).unwrap();
+        fs::write(dir.join("manifest.json"), b"{}").unwrap();
+        fs::write(dir.join("www/index.html"), b"<html></html>").unwrap();
+        fs::write(dir.join("www/css/site.css"), b"body {}").unwrap();
+    }
+
+    #[test]
+    fn reproducible_archives_are_identical() {
+        let tmp = std::env::temp_dir().join(format!("webber-archive-{}", std::process::id()));
+        let dir = tmp.join("data");
+        let build = |name: &str| {
+            write_files(&dir);
+            let tar_gz = tmp.join(format!("{}.tar.gz", name));
+            let ar = tmp.join(format!("{}.click", name));
+            create_tar_gz(&tar_gz, &dir, Some(1_600_000_000)).unwrap();
+            create_ar(&ar, &[(&tar_gz, "data.tar.gz")], Some(1_600_000_000)).unwrap();
+            (fs::read(tar_gz).unwrap(), fs::read(ar).unwrap())
+        };
+
+        let (tar_gz1, ar1) = build("first");
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let (tar_gz2, ar2) = build("second");
+        assert!(tar_gz1 == tar_gz2, "data.tar.gz differs between builds");
+        assert!(ar1 == ar2, "click package differs between builds");
+
+        let err = create_tar_gz(&tmp.join("late.tar.gz"), &dir, Some(1 << 32));
+        assert_eq!(err.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+}
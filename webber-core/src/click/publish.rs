@@ -0,0 +1,98 @@
+use regex::Regex;
+
+use super::lint::{is_valid_package_name, is_valid_version};
+
+/// The namespace of anonymous shortcuts, i.e. the suffix of their package names
+pub const DEFAULT_NAMESPACE: &str = "webber";
+const DEFAULT_MAINTAINER: &str = "Webber <noreply@ubports.com>";
+const DEFAULT_DESCRIPTION: &str = "Shortcut";
+
+/// Namespaces share the length budget of the webapp container's socket path with the appname
+/// (see `Package::appname`), so they are limited to leave room for the appname
+pub const NAMESPACE_MAX_LEN: usize = 24;
+
+/// The metadata of a package published in a store like the OpenStore, replacing the metadata of
+/// anonymous shortcuts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publishing {
+    /// The maintainer in the format `Name <email>`
+    pub maintainer: String,
+    pub description: String,
+    /// Replaces `webber` as suffix of the package name, usually the developer's or organization's
+    /// name as in `<appname>.<namespace>`
+    pub namespace: String,
+    /// The SPDX license expression of the package, e.g. `MIT`
+    pub license: String,
+}
+
+impl Publishing {
+    /// Check the metadata and the resulting package name `<appname>.<namespace>` against the
+    /// naming rules of click packages
+    pub fn validate(&self, appname: &str, version: &str) -> Result<(), String> {
+        let maintainer = Regex::new(r"^[^<>\n]*[^<>\s] <[^<>@\s]+@[^<>@\s]+>$").unwrap();
+        if !maintainer.is_match(&self.maintainer) {
+            return Err(format!(
+                "Maintainer \"{}\" is not in the format \"Name <email>\"",
+                self.maintainer
+            ));
+        }
+        if self.description.trim().is_empty() || self.description.contains('\n') {
+            return Err("The description has to be a single, non-empty line".to_owned());
+        }
+        if self.license.trim().is_empty() || self.license.contains('\n') {
+            return Err("The license has to be a single, non-empty line".to_owned());
+        }
+        if self.namespace.len() > NAMESPACE_MAX_LEN || !is_valid_package_name(&self.namespace) {
+            return Err(format!(
+                "Namespace \"{}\" may only contain lowercase letters, digits, '.', '+' and '-', \
+                 must start with a letter or digit and be 2 to {} characters long",
+                self.namespace, NAMESPACE_MAX_LEN
+            ));
+        }
+        let name = format!("{}.{}", appname, self.namespace);
+        if !is_valid_package_name(&name) {
+            return Err(format!("Invalid package name \"{}\"", name));
+        }
+        if !is_valid_version(version) {
+            return Err(format!(
+                "Version \"{}\" has to start with a digit and may only contain letters, digits, \
+                 '.', '+', '~' and '-'",
+                version
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The suffix of the package name
+pub(super) fn namespace(publishing: Option<&Publishing>) -> &str {
+    publishing.map_or(DEFAULT_NAMESPACE, |publishing| &publishing.namespace)
+}
+
+pub(super) fn maintainer(publishing: Option<&Publishing>) -> &str {
+    publishing.map_or(DEFAULT_MAINTAINER, |publishing| &publishing.maintainer)
+}
+
+pub(super) fn description(publishing: Option<&Publishing>) -> &str {
+    publishing.map_or(DEFAULT_DESCRIPTION, |publishing| &publishing.description)
+}
+
+/// Reconstruct the publishing metadata from the control manifest fields of a package, `None` for
+/// anonymous shortcuts
+pub(super) fn from_manifest(
+    name: &str,
+    maintainer: &str,
+    description: &str,
+    license: Option<&str>,
+) -> Option<Publishing> {
+    let namespace = name.split_once('.').map_or("", |(_, namespace)| namespace);
+    if namespace == DEFAULT_NAMESPACE && maintainer == DEFAULT_MAINTAINER {
+        return None;
+    }
+    Some(Publishing {
+        maintainer: maintainer.to_owned(),
+        description: description.to_owned(),
+        namespace: namespace.to_owned(),
+        license: license.unwrap_or_default().to_owned(),
+    })
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{mkdir, write_file, Package};
+
+/// The directory of the QML app within the app's directory of the click package
+pub(super) const QML_APP_DIR: &str = "app";
+
+const MAIN_QML: &str = include_str!("qmlapp/Main.qml");
+const PULL_TO_REFRESH_JS: &str = include_str!("qmlapp/pull-to-refresh.js");
+
+/// The program running a shortcut
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    /// Ubuntu Touch's webapp container
+    #[default]
+    WebappContainer,
+    /// A QML app based on Morph.Web, run by `qmlscene`. It offers pull-to-refresh, a header in
+    /// the theme color and opening external links in an overlay. Needs Ubuntu Touch 20.04 or
+    /// newer, as it is built on Lomiri.Components.
+    Qml,
+}
+
+impl Runtime {
+    pub const ALL: &'static [Runtime] = &[Runtime::WebappContainer, Runtime::Qml];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Runtime::WebappContainer => "webapp-container",
+            Runtime::Qml => "qml",
+        }
+    }
+}
+
+impl FromStr for Runtime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Runtime::ALL
+            .iter()
+            .copied()
+            .find(|runtime| runtime.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown runtime \"{}\", expected webapp-container or qml",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The settings of the shortcut baked into the QML app as `config.js`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct QmlAppConfig {
+    /// The click package name, which the app's storage is confined to
+    pub application_name: String,
+    /// The root directory of the click package relative to the QML app, which local urls like
+    /// those of offline snapshots are relative to
+    pub root: String,
+    /// Separates the web storage of the apps of a bundle
+    pub storage_name: String,
+    pub name: String,
+    pub url: String,
+    pub url_patterns: Vec<String>,
+    pub theme_color: String,
+    pub user_agent: String,
+    pub show_header: bool,
+    pub back_forward: bool,
+    pub fullscreen: bool,
+    pub pull_to_refresh: bool,
+    pub external_links: String,
+    pub store_session_cookies: bool,
+    /// The search path of the webapp model the user scripts are read back from
+    pub webapp_model: Option<String>,
+    /// The user scripts, relative to the QML app
+    pub user_scripts: Vec<String>,
+}
+
+impl QmlAppConfig {
+    fn to_js(&self) -> serde_json::Result<String> {
+        Ok(format!(
+            ".pragma library\n\nvar config = {};\n",
+            serde_json::to_string_pretty(self)?
+        ))
+    }
+
+    fn from_js(js: &str) -> Result<Self, String> {
+        let json = js
+            .split_once("var config = ")
+            .map(|(_, json)| json.trim_end().trim_end_matches(';'))
+            .ok_or("config.js contains no config")?;
+        serde_json::from_str(json).map_err(|err| format!("Invalid config.js: {}", err))
+    }
+}
+
+/// The command line of `qmlscene` running the QML app in `main_qml` at `url`
+pub(super) fn qml_exec(main_qml: &str, url: &str) -> Vec<String> {
+    vec!["qmlscene".to_owned(), main_qml.to_owned(), url.to_owned()]
+}
+
+/// The path of the main QML file of an app in the directory `prefix`
+pub(super) fn main_qml_path(prefix: &str) -> String {
+    format!("{}{}/Main.qml", prefix, QML_APP_DIR)
+}
+
+/// Write the QML app of `package` into `dir/QML_APP_DIR`. `prefix` is the path of `dir` within
+/// the click package, `webapp_model` the search path of the webapp model containing the
+/// `user_scripts`, which are relative to `dir`.
+pub(super) fn write_qml_app(
+    package: &Package,
+    dir: &Path,
+    package_name: &str,
+    prefix: &str,
+    webapp_model: Option<&str>,
+    user_scripts: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let app_dir = dir.join(QML_APP_DIR);
+    mkdir(&app_dir)?;
+
+    let config = QmlAppConfig {
+        application_name: package_name.to_owned(),
+        root: "../".repeat(prefix.matches('/').count() + 1),
+        storage_name: match prefix.trim_end_matches('/') {
+            "" => "Default".to_owned(),
+            subdir => subdir.to_owned(),
+        },
+        name: package.name.clone(),
+        url: package.url.clone(),
+        url_patterns: package
+            .url_patterns
+            .split(',')
+            .filter(|pat| !pat.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        theme_color: package.theme_color.clone(),
+        user_agent: package.user_agent.clone(),
+        show_header: package.enable_address_bar,
+        back_forward: package.enable_back_forward,
+        fullscreen: package.enable_fullscreen,
+        pull_to_refresh: package.container.pull_to_refresh,
+        external_links: package.container.external_links.name().to_owned(),
+        store_session_cookies: package.container.store_session_cookies,
+        webapp_model: webapp_model.map(str::to_owned),
+        user_scripts: user_scripts
+            .iter()
+            .map(|script| format!("../{}", script))
+            .collect(),
+    };
+
+    write_file(&app_dir.join("Main.qml"), MAIN_QML)?;
+    write_file(&app_dir.join("pull-to-refresh.js"), PULL_TO_REFRESH_JS)?;
+    write_file(&app_dir.join("config.js"), &config.to_js()?)?;
+    Ok(())
+}
+
+/// Read the config of the QML app whose main file is `main_qml`
+pub(super) fn read_qml_config(
+    data: &HashMap<String, Vec<u8>>,
+    main_qml: &str,
+) -> Result<QmlAppConfig, String> {
+    let path = match main_qml.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/config.js", dir),
+        None => "config.js".to_owned(),
+    };
+    match data.get(&path) {
+        Some(js) => QmlAppConfig::from_js(&String::from_utf8_lossy(js)),
+        None => Err(format!("Missing {}", path)),
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * The app of shortcuts using the QML runtime, generated by Webber. The settings of the shortcut
+ * are written to config.js.
+ */
+
+import QtQuick 2.9
+import QtQuick.Window 2.2
+import QtWebEngine 1.5
+import Lomiri.Components 1.3
+import Morph.Web 0.1
+import "config.js" as Config
+
+MainView {
+    id: root
+
+    readonly property var config: Config.config
+    // Desktop actions pass their url as last argument. Urls without scheme are files of the
+    // package, e.g. of an offline snapshot.
+    readonly property string startUrl: {
+        var args = Qt.application.arguments;
+        var url = args.length > 2 ? args[args.length - 1] : "";
+        if (/^https?:\/\//.test(url)) {
+            return url;
+        } else if (url !== "" && !/\.qml$/.test(url)) {
+            return Qt.resolvedUrl(config.root + url);
+        }
+        return config.url;
+    }
+
+    // Match a url against the webapp-container style url patterns, where `*` matches anything
+    // and `?` makes the preceding character optional
+    function isInternal(url) {
+        if (config.urlPatterns.length === 0 || url.indexOf("file://") === 0) {
+            return true;
+        }
+        for (var i = 0; i < config.urlPatterns.length; i++) {
+            var regex = config.urlPatterns[i]
+                .replace(/[.+^${}()|[\]\\\/]/g, "\\$&")
+                .replace(/\*/g, ".*");
+            if (new RegExp("^" + regex + "$").test(url)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    function openExternal(url) {
+        if (config.externalLinks === "overlay") {
+            overlayView.url = url;
+            overlay.visible = true;
+        } else {
+            Qt.openUrlExternally(url);
+        }
+    }
+
+    // Black or white, whichever is more readable on `color`
+    function textColor(color) {
+        var c = Qt.lighter(color, 1.0);
+        var luminance = 0.299 * c.r + 0.587 * c.g + 0.114 * c.b;
+        return luminance > 0.6 ? "#000000" : "#ffffff";
+    }
+
+    applicationName: config.applicationName
+    anchorToKeyboard: true
+    backgroundColor: config.themeColor || "#ffffff"
+
+    Component.onCompleted: {
+        if (config.fullscreen) {
+            root.Window.window.visibility = Window.FullScreen;
+        }
+    }
+
+    WebContext {
+        id: webContext
+        storageName: config.storageName
+        persistentCookiesPolicy: config.storeSessionCookies
+                                 ? WebEngineProfile.ForcePersistentCookies
+                                 : WebEngineProfile.AllowPersistentCookies
+
+        Component.onCompleted: {
+            if (config.userAgent !== "") {
+                httpUserAgent = config.userAgent;
+            }
+        }
+    }
+
+    PageHeader {
+        id: header
+        visible: config.showHeader || config.backForward
+        height: visible ? implicitHeight : 0
+        title: webView.title || config.name
+
+        StyleHints {
+            backgroundColor: root.backgroundColor
+            foregroundColor: root.textColor(root.backgroundColor)
+            dividerColor: root.backgroundColor
+        }
+
+        leadingActionBar.actions: config.backForward ? [
+            Action {
+                iconName: "go-previous"
+                text: i18n.tr("Back")
+                enabled: webView.canGoBack
+                onTriggered: webView.goBack()
+            }
+        ] : []
+
+        trailingActionBar.actions: config.backForward ? [
+            Action {
+                iconName: "reload"
+                text: i18n.tr("Reload")
+                onTriggered: webView.reload()
+            },
+            Action {
+                iconName: "go-next"
+                text: i18n.tr("Forward")
+                enabled: webView.canGoForward
+                onTriggered: webView.goForward()
+            }
+        ] : []
+    }
+
+    WebView {
+        id: webView
+
+        anchors {
+            top: header.bottom
+            left: parent.left
+            right: parent.right
+            bottom: parent.bottom
+        }
+        context: webContext
+
+        onNavigationRequested: {
+            var url = request.url.toString();
+            if (request.navigationType === WebEngineNavigationRequest.LinkClickedNavigation
+                    && !root.isInternal(url)) {
+                request.action = WebEngineNavigationRequest.IgnoreRequest;
+                root.openExternal(url);
+            }
+        }
+
+        onNewViewRequested: {
+            var url = request.requestedUrl.toString();
+            if (root.isInternal(url)) {
+                webView.url = url;
+            } else {
+                root.openExternal(url);
+            }
+        }
+
+        Component.onCompleted: {
+            var files = config.userScripts.slice();
+            if (config.pullToRefresh) {
+                files.push("pull-to-refresh.js");
+            }
+            var scripts = [];
+            for (var i = 0; i < files.length; i++) {
+                var script = Qt.createQmlObject(
+                    "import QtWebEngine 1.5; WebEngineScript {}", webView);
+                script.name = files[i];
+                script.sourceUrl = Qt.resolvedUrl(files[i]);
+                script.injectionPoint = WebEngineScript.DocumentReady;
+                script.worldId = WebEngineScript.MainWorld;
+                scripts.push(script);
+            }
+            userScripts = scripts;
+            url = root.startUrl;
+        }
+    }
+
+    // Shows links leaving the url patterns on top of the app
+    Rectangle {
+        id: overlay
+
+        anchors.fill: parent
+        visible: false
+        color: theme.palette.normal.background
+
+        PageHeader {
+            id: overlayHeader
+            title: overlayView.title
+
+            leadingActionBar.actions: [
+                Action {
+                    iconName: "close"
+                    text: i18n.tr("Close")
+                    onTriggered: {
+                        overlay.visible = false;
+                        overlayView.url = "about:blank";
+                    }
+                }
+            ]
+        }
+
+        WebView {
+            id: overlayView
+
+            anchors {
+                top: overlayHeader.bottom
+                left: parent.left
+                right: parent.right
+                bottom: parent.bottom
+            }
+            context: webContext
+        }
+    }
+}
@@ -0,0 +1,19 @@
+// Reload the page when it is pulled down while scrolled to the top
+(function () {
+    var threshold = 120;
+    var startY = null;
+
+    window.addEventListener("touchstart", function (event) {
+        startY = window.scrollY === 0 && event.touches.length === 1
+            ? event.touches[0].clientY
+            : null;
+    }, { passive: true });
+
+    window.addEventListener("touchend", function (event) {
+        if (startY !== null && window.scrollY === 0
+                && event.changedTouches[0].clientY - startY > threshold) {
+            location.reload();
+        }
+        startY = null;
+    }, { passive: true });
+})();
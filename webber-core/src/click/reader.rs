@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+use super::{
+    publish, qmlapp, snapshot, split_exec, supported_orientations, userscript, Action, AppArmor,
+    ContainerOptions, ControlManifest, DesktopEntry, DesktopGroup, Icon, LocalizedName,
+    ManifestHook, Package, PolicyTemplate, QmlAppConfig, Runtime, SnapshotManifest, Splash, Target,
+    DESKTOP_ENTRY_GROUP,
+};
+
+use crate::pwa::Orientation;
+
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+const AR_HEADER_LEN: usize = 60;
+
+/// The contents of an existing click package, as far as Webber understands them
+#[derive(Debug)]
+pub struct PackageContents {
+    /// The reconstructed package. Its icon is empty until `extract_icon` is called.
+    pub package: Package,
+    /// The name of the app hook, which identifies the shortcut across rebuilds
+    pub appname: String,
+    /// The file name of the embedded icon
+    pub icon_filename: String,
+    /// The raw contents of the embedded icon
+    pub icon_data: Vec<u8>,
+    /// The file name and contents of the embedded symbolic icon, if any
+    pub symbolic_icon: Option<(String, Vec<u8>)>,
+    /// The file name and contents of the embedded splash image, if any
+    pub splash_image: Option<(String, Vec<u8>)>,
+    /// The file names and contents of the icons of the package's actions, in the same order
+    pub action_icons: Vec<Option<(String, Vec<u8>)>>,
+}
+
+impl PackageContents {
+    /// Write the embedded icons to `dir` and point the package's icons at them. Returns the path
+    /// of the regular icon. Icons embedded into subdirectories, like the icons of bundled apps,
+    /// are written into `dir` itself.
+    pub fn extract_icon(&mut self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path_in = |filename: &str| match Path::new(filename).file_name() {
+            Some(name) => dir.join(name),
+            None => dir.join(filename),
+        };
+        if let Some((ref filename, ref data)) = self.symbolic_icon {
+            let path = path_in(filename);
+            fs::write(&path, data)?;
+            self.package.monochrome_icon = Some(Icon::Local(path.to_string_lossy().into_owned()));
+        }
+        if let Some((ref filename, ref data)) = self.splash_image {
+            let path = path_in(filename);
+            fs::write(&path, data)?;
+            self.package.splash.image = Some(Icon::Local(path.to_string_lossy().into_owned()));
+        }
+        for (action, icon) in self.package.actions.iter_mut().zip(&self.action_icons) {
+            if let Some((ref filename, ref data)) = icon {
+                let path = path_in(filename);
+                fs::write(&path, data)?;
+                action.icon = Some(Icon::Local(path.to_string_lossy().into_owned()));
+            }
+        }
+        let path = path_in(&self.icon_filename);
+        fs::write(&path, &self.icon_data)?;
+        self.package.icon = Icon::Local(path.to_string_lossy().into_owned());
+        Ok(path)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadPackageError {
+    IoError(io::Error),
+    FormatError(String),
+}
+
+impl fmt::Display for ReadPackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadPackageError::IoError(err) => write!(f, "IO error: {}", err),
+            ReadPackageError::FormatError(msg) => write!(f, "Format error: {}", msg),
+        }
+    }
+}
+
+impl Error for ReadPackageError {}
+
+impl From<io::Error> for ReadPackageError {
+    fn from(err: io::Error) -> Self {
+        ReadPackageError::IoError(err)
+    }
+}
+
+fn format_error<T>(msg: impl Into<String>) -> Result<T, ReadPackageError> {
+    Err(ReadPackageError::FormatError(msg.into()))
+}
+
+/// The files of the control and data archives of a click package, by relative path
+pub(super) struct PackageFiles {
+    pub control: HashMap<String, Vec<u8>>,
+    pub data: HashMap<String, Vec<u8>>,
+}
+
+impl PackageFiles {
+    pub fn read(path: &Path) -> Result<Self, ReadPackageError> {
+        let members = read_ar(&fs::read(path)?)?;
+
+        let control = match members.get("control.tar.gz") {
+            Some(control) => read_tar_gz(control)?,
+            None => return format_error("Missing control.tar.gz"),
+        };
+        let data = match members.get("data.tar.gz") {
+            Some(data) => read_tar_gz(data)?,
+            None => return format_error("Missing data.tar.gz"),
+        };
+
+        Ok(Self { control, data })
+    }
+
+    pub fn manifest(&self) -> Result<ControlManifest, ReadPackageError> {
+        match self.control.get("manifest") {
+            Some(manifest) => serde_json::from_slice(manifest)
+                .or_else(|err| format_error(format!("Invalid manifest: {}", err))),
+            None => format_error("Missing manifest"),
+        }
+    }
+}
+
+/// Read a click package built by Webber back into a `Package`
+pub fn read(path: &Path) -> Result<PackageContents, ReadPackageError> {
+    let files = PackageFiles::read(path)?;
+    let manifest = files.manifest()?;
+    let mut data = files.data;
+
+    let (appname, hook) = match manifest.hooks.iter().next() {
+        Some(hook) => hook,
+        None => return format_error("Manifest contains no hooks"),
+    };
+
+    read_hook(&manifest, &mut data, appname, hook)
+}
+
+/// Read every app of a click package built by Webber, e.g. of a `Bundle`, ordered by hook name
+pub fn read_bundle(path: &Path) -> Result<Vec<PackageContents>, ReadPackageError> {
+    let files = PackageFiles::read(path)?;
+    let manifest = files.manifest()?;
+    let mut data = files.data;
+
+    if manifest.hooks.is_empty() {
+        return format_error("Manifest contains no hooks");
+    }
+
+    manifest
+        .hooks
+        .iter()
+        .map(|(appname, hook)| read_hook(&manifest, &mut data, appname, hook))
+        .collect()
+}
+
+/// Read the app of the hook `appname`, taking its icons out of `data`
+fn read_hook(
+    manifest: &ControlManifest,
+    data: &mut HashMap<String, Vec<u8>>,
+    appname: &str,
+    hook: &ManifestHook,
+) -> Result<PackageContents, ReadPackageError> {
+    let apparmor: AppArmor = match data.get(&hook.apparmor) {
+        Some(apparmor) => serde_json::from_slice(apparmor)
+            .or_else(|err| format_error(format!("Invalid AppArmor policy: {}", err)))?,
+        None => return format_error(format!("Missing {}", hook.apparmor)),
+    };
+
+    let desktop = match data.get(&hook.desktop) {
+        Some(desktop) => DesktopEntry::parse(&String::from_utf8_lossy(desktop)),
+        None => return format_error(format!("Missing {}", hook.desktop)),
+    };
+    let entry = match desktop.group(DESKTOP_ENTRY_GROUP) {
+        Some(entry) => entry,
+        None => return format_error("Desktop file contains no [Desktop Entry] group"),
+    };
+
+    let mut exec = match entry.get("Exec") {
+        Some(exec) => parse_exec(&exec)?,
+        None => return format_error("Desktop entry contains no Exec key"),
+    };
+    if let Some(ref main_qml) = exec.qml_app {
+        let config = qmlapp::read_qml_config(data, main_qml).or_else(format_error)?;
+        exec.merge_qml_config(config);
+    }
+
+    // Offline snapshots are opened from the package, map their paths back to the site's urls
+    let prefix = match hook.desktop.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => String::new(),
+    };
+    let snapshot = if snapshot::is_local_url(&exec.url) {
+        Some(snapshot::read_snapshot(data, &prefix).or_else(format_error)?)
+    } else {
+        None
+    };
+    let site_url = |url: String| match snapshot {
+        Some(ref snapshot) => snapshot.site_url(&prefix, &url).unwrap_or(url),
+        None => url,
+    };
+
+    let icon_filename = entry.get("Icon").unwrap_or_default();
+    let icon_data = match data.remove(&icon_filename) {
+        Some(icon_data) => icon_data,
+        None => return format_error(format!("Missing icon {}", icon_filename)),
+    };
+
+    let user_scripts = match exec.webapp_model {
+        Some(ref search_path) => {
+            userscript::read_webapp_model(data, search_path).or_else(format_error)?
+        }
+        None => Vec::new(),
+    };
+
+    let mut take_file = |group: &DesktopGroup, key: &str| {
+        group.get(key).and_then(|filename| {
+            let data = data.remove(&filename)?;
+            Some((filename, data))
+        })
+    };
+    let symbolic_icon = take_file(entry, "X-Lomiri-Symbolic-Icon");
+    let splash_image = take_file(entry, "X-Lomiri-Splash-Image");
+
+    let mut actions = Vec::new();
+    let mut action_icons = Vec::new();
+    for id in entry.get_list("Actions") {
+        let group = match desktop.group(&format!("Desktop Action {}", id)) {
+            Some(group) => group,
+            None => return format_error(format!("Missing group of action {}", id)),
+        };
+        let url = match group.get("Exec") {
+            Some(exec) => site_url(parse_exec(&exec)?.url),
+            None => return format_error(format!("Action {} contains no Exec key", id)),
+        };
+        actions.push(Action {
+            name: group.get("Name").unwrap_or_default(),
+            url,
+            icon: None,
+        });
+        action_icons.push(take_file(group, "Icon"));
+    }
+
+    let permissions = apparmor
+        .policy_groups
+        .into_iter()
+        .filter(|group| group != "networking" && group != "webview")
+        .collect();
+    let policy_template = match apparmor.template.parse().unwrap_or_default() {
+        // The QML runtime needs the SDK template, which is implied when rebuilding
+        PolicyTemplate::UbuntuSdk if exec.container.runtime == Runtime::Qml => {
+            PolicyTemplate::default()
+        }
+        template => template,
+    };
+    let target = Target::from_framework(&manifest.framework).unwrap_or_default();
+
+    let package = Package {
+        url: site_url(exec.url),
+        name: entry.get("Name").unwrap_or_else(|| manifest.title.clone()),
+        names: LocalizedName::from_desktop_entry(entry),
+        // Packages built before splash screen customization used the theme color as splash color
+        theme_color: entry
+            .get("X-Webber-Theme-Color")
+            .or_else(|| entry.get("X-Lomiri-Splash-Color"))
+            .unwrap_or_default(),
+        // Embedded icons are already cropped and composited
+        background_color: String::new(),
+        icon: Icon::Local(String::new()),
+        maskable_icon: false,
+        monochrome_icon: None,
+        splash: Splash::from_desktop_entry(entry),
+        orientation: entry
+            .get("X-Lomiri-Supported-Orientations")
+            .and_then(|value| {
+                Orientation::ALL
+                    .iter()
+                    .copied()
+                    .find(|&orientation| supported_orientations(orientation) == value.as_str())
+            }),
+        actions,
+        url_patterns: match snapshot {
+            Some(_) => exec
+                .url_patterns
+                .split(',')
+                .filter(|pat| *pat != snapshot::snapshot_url_pattern(&prefix))
+                .collect::<Vec<_>>()
+                .join(","),
+            None => exec.url_patterns,
+        },
+        permissions,
+        policy_template,
+        read_paths: apparmor.read_path,
+        write_paths: apparmor.write_path,
+        enable_address_bar: exec.enable_address_bar,
+        enable_back_forward: exec.enable_back_forward,
+        enable_fullscreen: exec.enable_fullscreen,
+        user_agent: exec.user_agent,
+        container: ContainerOptions {
+            // Targets without media-hub never pass the flag, keep it for retargeted rebuilds
+            media_hub_audio: exec.container.media_hub_audio || !target.media_hub_audio(),
+            // Implied by offline snapshots
+            local_content_remote_access: exec.container.local_content_remote_access
+                && snapshot.is_none(),
+            ..exec.container
+        },
+        user_scripts,
+        offline_snapshot: snapshot.as_ref().map(SnapshotManifest::options),
+        target,
+        version: manifest.version.clone(),
+        reproducible: false,
+        publishing: publish::from_manifest(
+            &manifest.name,
+            &manifest.maintainer,
+            &manifest.description,
+            manifest.license.as_deref(),
+        ),
+    };
+
+    Ok(PackageContents {
+        package,
+        appname: appname.to_owned(),
+        icon_filename,
+        icon_data,
+        symbolic_icon,
+        splash_image,
+        action_icons,
+    })
+}
+
+/// Parse a (GNU) ar archive into a map of member names to member contents
+fn read_ar(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>, ReadPackageError> {
+    if !bytes.starts_with(AR_MAGIC) {
+        return format_error("Not an ar archive");
+    }
+
+    let mut members = HashMap::new();
+    let mut offset = AR_MAGIC.len();
+
+    while offset + AR_HEADER_LEN <= bytes.len() {
+        let header = &bytes[offset..offset + AR_HEADER_LEN];
+        if &header[58..60] != b"`\n" {
+            return format_error("Invalid ar member header");
+        }
+        let name = String::from_utf8_lossy(&header[0..16]);
+        let name = name.trim_end().trim_end_matches('/').to_owned();
+        let size = String::from_utf8_lossy(&header[48..58])
+            .trim()
+            .parse::<usize>()
+            .or_else(|_| format_error("Invalid ar member size"))?;
+
+        let start = offset + AR_HEADER_LEN;
+        let end = start + size;
+        if end > bytes.len() {
+            return format_error("Truncated ar archive");
+        }
+        members.insert(name, bytes[start..end].to_vec());
+
+        // Members are aligned to even offsets
+        offset = end + size % 2;
+    }
+
+    Ok(members)
+}
+
+/// Unpack a gzipped tar archive into a map of relative file paths to file contents
+fn read_tar_gz(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>, ReadPackageError> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let mut files = HashMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()?
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .collect::<PathBuf>();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        files.insert(path.to_string_lossy().into_owned(), contents);
+    }
+
+    Ok(files)
+}
+
+#[derive(Default)]
+pub(super) struct ExecFlags {
+    pub url: String,
+    pub url_patterns: String,
+    pub enable_address_bar: bool,
+    pub enable_back_forward: bool,
+    pub enable_fullscreen: bool,
+    pub user_agent: String,
+    pub container: ContainerOptions,
+    /// The search path of the webapp model containing the user scripts
+    pub webapp_model: Option<String>,
+    /// The main QML file of an app using the QML runtime
+    pub qml_app: Option<String>,
+}
+
+impl ExecFlags {
+    /// Take the settings of an app using the QML runtime from its `config`
+    pub(super) fn merge_qml_config(&mut self, config: QmlAppConfig) {
+        self.url_patterns = config.url_patterns.join(",");
+        self.enable_address_bar = config.show_header;
+        self.enable_back_forward = config.back_forward;
+        self.enable_fullscreen = config.fullscreen;
+        self.user_agent = config.user_agent;
+        self.webapp_model = config.webapp_model;
+        self.container = ContainerOptions {
+            runtime: Runtime::Qml,
+            pull_to_refresh: config.pull_to_refresh,
+            store_session_cookies: config.store_session_cookies,
+            external_links: config.external_links.parse().unwrap_or_default(),
+            // Options of the webapp container only, keep their defaults for rebuilds
+            ..ContainerOptions::default()
+        };
+    }
+}
+
+/// Parse the `webapp-container` or `qmlscene` command line of a desktop entry
+pub(super) fn parse_exec(exec: &str) -> Result<ExecFlags, ReadPackageError> {
+    let mut args = split_exec(exec)
+        .or_else(|err| format_error(format!("Invalid Exec key: {}", err)))?
+        .into_iter();
+    let mut flags = ExecFlags {
+        container: ContainerOptions::disabled(),
+        ..Default::default()
+    };
+
+    let qml = match args.next() {
+        Some(program) if program == "webapp-container" => false,
+        Some(program) if program == "qmlscene" => true,
+        _ => return format_error("Not a webapp-container shortcut"),
+    };
+
+    for arg in args {
+        if qml && flags.qml_app.is_none() && arg.ends_with(".qml") {
+            flags.qml_app = Some(arg);
+        } else if let Some(patterns) = arg.strip_prefix("--webappUrlPatterns=") {
+            flags.url_patterns = patterns.to_owned();
+        } else if let Some(search_path) = arg.strip_prefix("--webappModelSearchPath=") {
+            flags.webapp_model = Some(search_path.to_owned());
+        } else if let Some(user_agent) = arg.strip_prefix("--user-agent-string=") {
+            flags.user_agent = user_agent.to_owned();
+        } else if arg == "--enable-addressbar" {
+            flags.enable_address_bar = true;
+        } else if arg == "--enable-back-forward" {
+            flags.enable_back_forward = true;
+        } else if arg == "--fullscreen" {
+            flags.enable_fullscreen = true;
+        } else if !flags.container.parse_arg(&arg) && !arg.starts_with("--") {
+            flags.url = arg;
+        }
+    }
+
+    if flags.url.is_empty() {
+        return format_error("Exec contains no url");
+    }
+    if qml && flags.qml_app.is_none() {
+        return format_error("Exec contains no QML file");
+    }
+
+    Ok(flags)
+}
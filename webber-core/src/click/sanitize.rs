@@ -0,0 +1,344 @@
+use csscolorparser::Color;
+use url::Url;
+
+use super::{Package, Runtime, Target};
+
+/// Characters that reorder the surrounding text and can make a name look like something else
+const BIDI_OVERRIDES: &[char] = &[
+    '\u{202a}', '\u{202b}', '\u{202c}', '\u{202d}', '\u{202e}', '\u{2066}', '\u{2067}', '\u{2068}',
+    '\u{2069}',
+];
+
+impl Package {
+    /// Clean up the free text settings, which usually come from scraped sites: control
+    /// characters are stripped, line breaks and tabs become spaces. Names are trimmed, as Ubuntu
+    /// Touch's app grid has problems displaying apps whose names include leading whitespace.
+    pub fn sanitize(&mut self) {
+        self.name = sanitize_text(&self.name);
+        for name in &mut self.names {
+            name.name = sanitize_text(&name.name);
+        }
+        self.names.retain(|name| !name.name.is_empty());
+        for action in &mut self.actions {
+            action.name = sanitize_text(&action.name);
+        }
+        self.splash.title = sanitize_text(&self.splash.title);
+        self.url = self.url.trim().to_owned();
+        self.url_patterns = self.url_patterns.trim().to_owned();
+        self.user_agent = self.user_agent.trim().to_owned();
+    }
+
+    /// Check every setting that ends up in the desktop entry. Fails on control characters, which
+    /// `sanitize` strips from free text, and on colors, urls and url patterns that are invalid.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("The name is empty".to_owned());
+        }
+        validate_text("name", &self.name)?;
+        for name in &self.names {
+            validate_text(&format!("name for {}", name.locale), &name.name)?;
+            if name.locale.is_empty()
+                || !name
+                    .locale
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_@.-".contains(c))
+            {
+                return Err(format!("Invalid locale \"{}\"", name.locale.escape_debug()));
+            }
+        }
+
+        validate_url("url", &self.url)?;
+        for pattern in self.url_patterns.split(',').filter(|pat| !pat.is_empty()) {
+            validate_url_pattern(pattern)?;
+        }
+        for action in &self.actions {
+            validate_text("shortcut name", &action.name)?;
+            validate_url(&format!("url of shortcut {}", action.name), &action.url)?;
+        }
+
+        validate_color("theme color", &self.theme_color)?;
+        validate_color("background color", &self.background_color)?;
+        validate_color("splash color", &self.splash.color)?;
+        validate_color("splash header color", &self.splash.header_color)?;
+        validate_text("splash title", &self.splash.title)?;
+
+        // The QML app is built on Lomiri.Components, 16.04 only ships Ubuntu.Components
+        if self.container.runtime == Runtime::Qml && self.target == Target::Xenial {
+            return Err(format!(
+                "The qml runtime needs Ubuntu Touch 20.04 or newer, use webapp-container for {}",
+                self.target.version()
+            ));
+        }
+
+        validate_text("User-Agent", &self.user_agent)?;
+        for script in &self.user_scripts {
+            validate_text("user script name", &script.name)?;
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn sanitize_text(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() || BIDI_OVERRIDES.contains(&c) => None,
+            c => Some(c),
+        })
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+fn validate_text(what: &str, text: &str) -> Result<(), String> {
+    if text
+        .chars()
+        .any(|c| c.is_control() || BIDI_OVERRIDES.contains(&c))
+    {
+        return Err(format!(
+            "The {} \"{}\" contains control characters",
+            what,
+            text.escape_debug()
+        ));
+    }
+    Ok(())
+}
+
+/// Empty colors fall back to a default color
+fn validate_color(what: &str, color: &str) -> Result<(), String> {
+    if color.is_empty() {
+        return Ok(());
+    }
+    color.parse::<Color>().map(|_| ()).map_err(|_| {
+        format!(
+            "Invalid {} \"{}\", expected a CSS color like #rrggbb or rgb(r, g, b)",
+            what,
+            color.escape_debug()
+        )
+    })
+}
+
+fn validate_url(what: &str, url: &str) -> Result<(), String> {
+    // Url::parse silently drops line breaks and tabs, so check for them first
+    validate_text(what, url)?;
+    let parsed = Url::parse(url).map_err(|err| format!("Invalid {} \"{}\": {}", what, url, err))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(format!(
+            "Invalid {} \"{}\": {} urls are not supported",
+            what, url, scheme
+        )),
+    }
+}
+
+/// Url patterns are urls where `*` matches anything and `?` makes the preceding character
+/// optional, see `url_pattern_matches`
+fn validate_url_pattern(pattern: &str) -> Result<(), String> {
+    if pattern.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "Invalid url pattern \"{}\": contains whitespace",
+            pattern.escape_debug()
+        ));
+    }
+    let example = pattern.replace('?', "").replace('*', "x");
+    validate_url("url pattern", &example)
+        .map_err(|_| format!("Invalid url pattern \"{}\"", pattern.escape_debug()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{data_desktop_entry, Action, EmbeddedIcons, LocalizedName};
+    use super::super::{DesktopEntry, DESKTOP_ENTRY_GROUP};
+    use super::*;
+
+    fn package(name: &str) -> Package {
+        Package {
+            url: "https://example.com/".to_owned(),
+            name: name.to_owned(),
+            theme_color: "#123456".to_owned(),
+            url_patterns: "https?://example.com/*".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn desktop_entry(package: &Package) -> DesktopEntry {
+        let icons = EmbeddedIcons {
+            icon: "icon.svg".to_owned(),
+            symbolic_icon: None,
+            splash_image: None,
+            actions: package.actions.iter().map(|_| None).collect(),
+        };
+        DesktopEntry::parse(&data_desktop_entry(package, &icons, "", None, None).to_string())
+    }
+
+    #[test]
+    fn newline_in_title_cannot_inject_keys() {
+        let mut shortcut = package("Site\nExec=sh -c 'rm -rf ~'\n[Desktop Action evil]");
+        assert!(shortcut.validate().is_err());
+
+        shortcut.sanitize();
+        assert_eq!(
+            shortcut.name,
+            "Site Exec=sh -c 'rm -rf ~' [Desktop Action evil]"
+        );
+        shortcut.validate().unwrap();
+
+        let content = data_desktop_entry(
+            &shortcut,
+            &EmbeddedIcons {
+                icon: "icon.svg".to_owned(),
+                symbolic_icon: None,
+                splash_image: None,
+                actions: Vec::new(),
+            },
+            "",
+            None,
+            None,
+        )
+        .to_string();
+        assert_eq!(content.matches("\nExec=").count(), 1);
+        assert_eq!(content.matches("\n[").count(), 0);
+    }
+
+    #[test]
+    fn hostile_values_stay_in_their_key() {
+        // Even without sanitizing, escaping keeps every value within its key
+        let mut shortcut = package("100% \"Evil\" back\\slash\nExec=evil");
+        shortcut.user_agent = "UA \"quoted\" $HOME `id` %u \\".to_owned();
+        shortcut.url_patterns = "https?://example.com/a;b*,https?://*.example.com/%20*".to_owned();
+        shortcut.actions.push(Action {
+            name: "Compose\nExec=evil".to_owned(),
+            url: "https://example.com/new?a=1&b=$2".to_owned(),
+            icon: None,
+        });
+
+        let entry = desktop_entry(&shortcut);
+        let group = entry.group(DESKTOP_ENTRY_GROUP).unwrap();
+        assert_eq!(group.get("Name").unwrap(), shortcut.name);
+        assert_eq!(entry.groups().count(), 2);
+
+        let exec = super::super::split_exec(&group.get("Exec").unwrap()).unwrap();
+        assert_eq!(exec[0], "webapp-container");
+        assert!(exec.contains(&format!("--webappUrlPatterns={}", shortcut.url_patterns)));
+        assert!(exec.contains(&format!("--user-agent-string={}", shortcut.user_agent)));
+        assert_eq!(exec.last().unwrap(), &shortcut.url);
+
+        let action = entry.group("Desktop Action shortcut-1").unwrap();
+        assert_eq!(action.get("Name").unwrap(), shortcut.actions[0].name);
+        let exec = super::super::split_exec(&action.get("Exec").unwrap()).unwrap();
+        assert_eq!(exec.last().unwrap(), &shortcut.actions[0].url);
+    }
+
+    #[test]
+    fn sanitize_strips_control_characters() {
+        let mut shortcut = package("\u{202e}gpj.exe\u{0} Site\r\n");
+        shortcut.names.push(LocalizedName {
+            locale: "de".to_owned(),
+            name: "Seite\u{7}\tzwei".to_owned(),
+            rtl: false,
+        });
+        shortcut.names.push(LocalizedName {
+            locale: "fr".to_owned(),
+            name: "\n\u{1b}".to_owned(),
+            rtl: false,
+        });
+        shortcut.splash.title = "Title\u{8}\n".to_owned();
+        shortcut.sanitize();
+
+        assert_eq!(shortcut.name, "gpj.exe Site");
+        assert_eq!(shortcut.names.len(), 1);
+        assert_eq!(shortcut.names[0].name, "Seite zwei");
+        assert_eq!(shortcut.splash.title, "Title");
+        shortcut.validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        for color in &["#12345g", "#fff\nExec=evil", "red;Exec=evil", "rgb(1, 2)"] {
+            let mut shortcut = package("Site");
+            shortcut.theme_color = color.to_string();
+            assert!(shortcut.validate().is_err(), "{:?}", color);
+
+            let mut shortcut = package("Site");
+            shortcut.splash.header_color = color.to_string();
+            assert!(shortcut.validate().is_err(), "{:?}", color);
+        }
+
+        let mut shortcut = package("Site");
+        shortcut.theme_color = "#abc".to_owned();
+        shortcut.background_color = "rgb(1, 2, 3)".to_owned();
+        shortcut.validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_urls() {
+        for url in &[
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "https://example.com/\nExec=evil",
+            "https://example.com/\t",
+            "example.com",
+            "",
+        ] {
+            let mut shortcut = package("Site");
+            shortcut.url = url.to_string();
+            assert!(shortcut.validate().is_err(), "{:?}", url);
+
+            let mut shortcut = package("Site");
+            shortcut.actions.push(Action {
+                name: "Action".to_owned(),
+                url: url.to_string(),
+                icon: None,
+            });
+            assert!(shortcut.validate().is_err(), "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_url_patterns() {
+        for patterns in &[
+            "https?://example.com/*\nExec=evil",
+            "https?://exa mple.com/*",
+            "javascript:*",
+            "https?://example.com/*,*",
+        ] {
+            let mut shortcut = package("Site");
+            shortcut.url_patterns = patterns.to_string();
+            assert!(shortcut.validate().is_err(), "{:?}", patterns);
+        }
+
+        let mut shortcut = package("Site");
+        shortcut.url_patterns = "https?://*.example.com/*,http://example.com:8080/a?b/*".to_owned();
+        shortcut.validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_control_characters_in_other_values() {
+        let mut shortcut = package("Site");
+        shortcut.user_agent = "Mozilla/5.0\nExec=evil".to_owned();
+        assert!(shortcut.validate().is_err());
+
+        let mut shortcut = package("Site");
+        shortcut.names.push(LocalizedName {
+            locale: "de]\nExec=evil".to_owned(),
+            name: "Seite".to_owned(),
+            rtl: false,
+        });
+        assert!(shortcut.validate().is_err());
+
+        assert!(package("").validate().is_err());
+    }
+
+    #[test]
+    fn rejects_the_qml_runtime_for_xenial() {
+        let mut shortcut = package("Site");
+        shortcut.container.runtime = Runtime::Qml;
+        shortcut.validate().unwrap();
+
+        shortcut.target = Target::Xenial;
+        assert!(shortcut.validate().is_err());
+
+        shortcut.container.runtime = Runtime::WebappContainer;
+        shortcut.validate().unwrap();
+    }
+}
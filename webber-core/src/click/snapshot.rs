@@ -0,0 +1,854 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+
+use regex::{Captures, Regex};
+use reqwest::blocking as reqwest;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{mkdir, write_file};
+
+/// The directory of the offline snapshot within the app's directory of the click package
+pub(super) const SNAPSHOT_DIR: &str = "www";
+/// Maps the files of the snapshot back to the site's urls, next to `SNAPSHOT_DIR`
+const SNAPSHOT_MANIFEST: &str = "snapshot.json";
+/// Further files of a site are left out of the snapshot, to bound crawling large sites
+const MAX_FILES: usize = 1000;
+
+/// The size budget of offline snapshots if none is given, in bytes
+pub const DEFAULT_SNAPSHOT_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Ship the static files of the site in the package, so the app works without network. Meant
+/// for static single-page apps, server-side features won't work in the snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OfflineSnapshot {
+    /// The maximum total size of the files in bytes, further files are left out
+    pub max_size: u64,
+}
+
+impl Default for OfflineSnapshot {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_SNAPSHOT_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Html,
+    Css,
+    Other,
+}
+
+impl FileKind {
+    fn detect(content_type: &str, url: &Url) -> Self {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match mime.as_str() {
+            "text/html" | "application/xhtml+xml" => FileKind::Html,
+            "text/css" => FileKind::Css,
+            "" | "application/octet-stream" => {
+                let path = url.path().to_ascii_lowercase();
+                if path.ends_with('/') || path.ends_with(".html") || path.ends_with(".htm") {
+                    FileKind::Html
+                } else if path.ends_with(".css") {
+                    FileKind::Css
+                } else {
+                    FileKind::Other
+                }
+            }
+            _ => FileKind::Other,
+        }
+    }
+}
+
+/// A file of the site and its path within `SNAPSHOT_DIR`
+struct SnapshotFile {
+    /// The url the file was found at, after redirects
+    url: Url,
+    kind: FileKind,
+    data: Vec<u8>,
+    path: String,
+}
+
+/// The references to other files in HTML and CSS
+struct ReferencePatterns {
+    html: Regex,
+    /// `srcset` attributes, which list several urls
+    srcset: Regex,
+    css: [Regex; 2],
+}
+
+impl ReferencePatterns {
+    fn new() -> Self {
+        // Attribute values may be quoted or not
+        let attribute = |names: &str| {
+            Regex::new(&format!(
+                r#"(?is)(\s(?:{})\s*=\s*)(?:"([^"]*)"|'([^']*)'|([^\s"'<>`=]+))"#,
+                names
+            ))
+            .unwrap()
+        };
+        Self {
+            html: attribute("src|href|poster"),
+            srcset: attribute("srcset"),
+            css: [
+                Regex::new(r#"(?is)url\(\s*(?:"([^"]*)"|'([^']*)'|([^)"'\s]*))\s*\)"#).unwrap(),
+                Regex::new(r#"(?is)@import\s+(?:"([^"]*)"|'([^']*)')"#).unwrap(),
+            ],
+        }
+    }
+
+    fn of(&self, kind: FileKind) -> Vec<&Regex> {
+        match kind {
+            // Covers `<style>` elements and `style` attributes
+            FileKind::Html => vec![&self.html, &self.css[0], &self.css[1]],
+            FileKind::Css => self.css.iter().collect(),
+            FileKind::Other => Vec::new(),
+        }
+    }
+
+    /// The values of all references in `text`, a file of `kind`
+    fn values<'t>(&self, kind: FileKind, text: &'t str) -> Vec<&'t str> {
+        let mut values = self
+            .of(kind)
+            .into_iter()
+            .flat_map(|regex| references(text, regex))
+            .collect::<Vec<_>>();
+        if kind == FileKind::Html {
+            for srcset in references(text, &self.srcset) {
+                values.extend(srcset_urls(srcset).into_iter().map(|range| &srcset[range]));
+            }
+        }
+        values
+    }
+
+    /// Replace the values of all references in `text`, a file of `kind`, see
+    /// `replace_references`
+    fn replace(
+        &self,
+        kind: FileKind,
+        text: &str,
+        mut map: impl FnMut(&str) -> Option<String>,
+    ) -> String {
+        let mut text = text.to_owned();
+        for regex in self.of(kind) {
+            text = replace_references(&text, regex, &mut map);
+        }
+        if kind == FileKind::Html {
+            text = replace_references(&text, &self.srcset, |srcset| {
+                let mut replaced = srcset.to_owned();
+                for range in srcset_urls(srcset).into_iter().rev() {
+                    if let Some(reference) = map(&srcset[range.clone()]) {
+                        // Commas separate the candidates of the list
+                        replaced.replace_range(range, &reference.replace(',', "%2C"));
+                    }
+                }
+                Some(replaced)
+            });
+        }
+        text
+    }
+}
+
+/// The static files of a site, crawled from its start url
+pub(super) struct SiteSnapshot {
+    files: Vec<SnapshotFile>,
+    /// The paths of the files by their url, without fragment
+    paths: HashMap<Url, String>,
+    /// Why files were left out of the snapshot
+    pub warnings: Vec<String>,
+}
+
+impl SiteSnapshot {
+    /// Download the files of the same origin reachable from `url`, breadth-first until the size
+    /// budget of `options` is used up
+    pub fn crawl(url: &str, options: &OfflineSnapshot) -> Result<Self, Box<dyn Error>> {
+        let start = without_fragment(&Url::parse(url)?);
+        let client = reqwest::Client::new();
+        let patterns = ReferencePatterns::new();
+
+        let mut queue = VecDeque::from(vec![start.clone()]);
+        let mut seen = HashSet::new();
+        seen.insert(start.clone());
+        let mut snapshot = SiteSnapshot {
+            files: Vec::new(),
+            paths: HashMap::new(),
+            warnings: Vec::new(),
+        };
+        let mut size = 0;
+
+        while let Some(url) = queue.pop_front() {
+            if snapshot.files.len() >= MAX_FILES {
+                snapshot.warnings.push(format!(
+                    "Leaving the remaining files out of the offline snapshot, \
+                     it is limited to {} files",
+                    MAX_FILES
+                ));
+                break;
+            }
+            // Directories and their index.html are the same page
+            if let Some(path) = index_alias(&url).and_then(|alias| snapshot.paths.get(&alias)) {
+                let path = path.clone();
+                snapshot.paths.insert(url, path);
+                continue;
+            }
+            let (final_url, kind, data) =
+                match fetch(&client, &url, &start, options.max_size - size) {
+                    Ok(Some(file)) => file,
+                    Ok(None) if snapshot.files.is_empty() => {
+                        return Err(format!(
+                            "{} exceeds the size budget of the offline snapshot ({} bytes)",
+                            url, options.max_size
+                        )
+                        .into());
+                    }
+                    Ok(None) => {
+                        snapshot.warnings.push(format!(
+                            "Leaving {} out of the offline snapshot, it exceeds the size budget",
+                            url
+                        ));
+                        continue;
+                    }
+                    Err(err) if snapshot.files.is_empty() => return Err(err),
+                    Err(err) => {
+                        snapshot.warnings.push(format!(
+                            "Leaving {} out of the offline snapshot: {}",
+                            url, err
+                        ));
+                        continue;
+                    }
+                };
+            // Another url redirected to the same file
+            if let Some(path) = snapshot.paths.get(&without_fragment(&final_url)).cloned() {
+                snapshot.paths.insert(url, path);
+                continue;
+            }
+            size += data.len() as u64;
+
+            let text = String::from_utf8_lossy(&data);
+            for value in patterns.values(kind, &text) {
+                let link = match resolve_reference(&final_url, value, kind) {
+                    Some(link) => without_fragment(&link),
+                    None => continue,
+                };
+                if link.origin() == start.origin() && seen.insert(link.clone()) {
+                    queue.push_back(link);
+                }
+            }
+
+            let path = unique_path(&snapshot.files, local_path(&final_url, kind));
+            snapshot.paths.insert(url, path.clone());
+            snapshot
+                .paths
+                .insert(without_fragment(&final_url), path.clone());
+            snapshot.files.push(SnapshotFile {
+                url: final_url,
+                kind,
+                data,
+                path,
+            });
+        }
+
+        Ok(snapshot)
+    }
+
+    /// The path of the snapshot of `url` within `SNAPSHOT_DIR`, `None` if it wasn't saved. The
+    /// fragment is dropped, as containers expect a file path.
+    pub fn local_path(&self, url: &str) -> Option<&str> {
+        let url = without_fragment(&Url::parse(url).ok()?);
+        self.paths.get(&url).map(String::as_str)
+    }
+
+    /// Write the files into `dir/SNAPSHOT_DIR`, pointing their references to other files of the
+    /// snapshot at the local copies
+    pub fn write(&self, dir: &Path, options: &OfflineSnapshot) -> Result<(), Box<dyn Error>> {
+        let www = dir.join(SNAPSHOT_DIR);
+        mkdir(&www)?;
+        let patterns = ReferencePatterns::new();
+
+        let mut manifest = SnapshotManifest {
+            max_size: options.max_size,
+            files: BTreeMap::new(),
+        };
+        for file in &self.files {
+            let path = www.join(&file.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match file.kind {
+                FileKind::Other => fs::write(&path, &file.data)?,
+                kind => {
+                    let text =
+                        patterns.replace(kind, &String::from_utf8_lossy(&file.data), |value| {
+                            self.rewrite_reference(file, value)
+                        });
+                    write_file(&path, &text)?;
+                }
+            }
+            manifest
+                .files
+                .insert(file.path.clone(), file.url.as_str().to_owned());
+        }
+
+        write_file(
+            &dir.join(SNAPSHOT_MANIFEST),
+            &serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+
+    /// The reference `value` in `file` pointing at the local copy if there is one. Other
+    /// references are made absolute, so they still work while online.
+    fn rewrite_reference(&self, file: &SnapshotFile, value: &str) -> Option<String> {
+        let target = resolve_reference(&file.url, value, file.kind)?;
+        let reference = match self.paths.get(&without_fragment(&target)) {
+            Some(path) => {
+                let mut reference = relative_path(&file.path, path);
+                if let Some(fragment) = target.fragment() {
+                    reference.push('#');
+                    reference.push_str(fragment);
+                }
+                reference
+            }
+            None => target.into(),
+        };
+        // Keep the reference from breaking out of its quotes
+        let reference = reference
+            .replace('"', "%22")
+            .replace('\'', "%27")
+            .replace('(', "%28")
+            .replace(')', "%29");
+        Some(match file.kind {
+            FileKind::Html => reference.replace('&', "&amp;"),
+            _ => reference,
+        })
+    }
+}
+
+/// A downloaded file: the url it was found at, its kind and its contents
+type Fetched = (Url, FileKind, Vec<u8>);
+
+/// Fetch `url`, returning `None` for files larger than `budget` bytes without downloading more
+/// than that
+fn fetch(
+    client: &reqwest::Client,
+    url: &Url,
+    start: &Url,
+    budget: u64,
+) -> Result<Option<Fetched>, Box<dyn Error>> {
+    let resp = client.get(url.as_str()).send()?.error_for_status()?;
+    let final_url = resp.url().clone();
+    if final_url.origin() != start.origin() {
+        return Err(format!("{} redirects to another site ({})", url, final_url).into());
+    }
+    let content_type = resp
+        .headers()
+        .get(::reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let kind = FileKind::detect(&content_type, &final_url);
+
+    if resp
+        .content_length()
+        .map_or(false, |length| length > budget)
+    {
+        return Ok(None);
+    }
+    // The content length is missing for chunked responses and may be wrong
+    let mut data = Vec::new();
+    resp.take(budget.saturating_add(1)).read_to_end(&mut data)?;
+    if data.len() as u64 > budget {
+        return Ok(None);
+    }
+    Ok(Some((final_url, kind, data)))
+}
+
+/// The values of the references matched by `regex` in `text`. The value is the last group
+/// taking part in the match.
+fn references<'t>(text: &'t str, regex: &Regex) -> Vec<&'t str> {
+    regex
+        .captures_iter(text)
+        .filter_map(|caps| caps.iter().skip(1).flatten().last())
+        .map(|value| value.as_str())
+        .collect()
+}
+
+/// Replace the values of the references matched by `regex` in `text` by the result of `map`,
+/// keeping the references `map` returns `None` for
+fn replace_references(
+    text: &str,
+    regex: &Regex,
+    mut map: impl FnMut(&str) -> Option<String>,
+) -> String {
+    regex
+        .replace_all(text, |caps: &Captures| {
+            let whole = caps.get(0).unwrap();
+            let replaced = caps
+                .iter()
+                .skip(1)
+                .flatten()
+                .last()
+                .and_then(|value| Some((value, map(value.as_str())?)));
+            match replaced {
+                Some((value, reference)) => format!(
+                    "{}{}{}",
+                    &text[whole.start()..value.start()],
+                    reference,
+                    &text[value.end()..whole.end()]
+                ),
+                None => whole.as_str().to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+/// The byte ranges of the urls in the `srcset` attribute value `srcset`, parsed like browsers do:
+/// candidates are separated by commas and consist of a url and optional descriptors
+fn srcset_urls(srcset: &str) -> Vec<Range<usize>> {
+    let bytes = srcset.as_bytes();
+    let mut urls = Vec::new();
+    let mut pos = 0;
+    loop {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b',') {
+            pos += 1;
+        }
+        if pos == bytes.len() {
+            return urls;
+        }
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let mut end = pos;
+        if bytes[end - 1] == b',' {
+            // A url without descriptors, the trailing commas end the candidate
+            while end > start && bytes[end - 1] == b',' {
+                end -= 1;
+            }
+        } else {
+            // The descriptors end at the next comma outside of parentheses
+            let mut depth = 0usize;
+            while pos < bytes.len() {
+                match bytes[pos] {
+                    b'(' => depth += 1,
+                    b')' => depth = depth.saturating_sub(1),
+                    b',' if depth == 0 => break,
+                    _ => {}
+                }
+                pos += 1;
+            }
+        }
+        if end > start {
+            urls.push(start..end);
+        }
+    }
+}
+
+/// The http(s) url the reference `value` in a file of `kind` at `base` points to
+fn resolve_reference(base: &Url, value: &str, kind: FileKind) -> Option<Url> {
+    let value = match kind {
+        FileKind::Html => value.replace("&amp;", "&"),
+        _ => value.to_owned(),
+    };
+    let value = value.trim();
+    if value.is_empty() || value.starts_with('#') {
+        return None;
+    }
+    base.join(value)
+        .ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+}
+
+/// `dir/index.html` for the url `dir/` and vice versa
+fn index_alias(url: &Url) -> Option<Url> {
+    let path = url.path();
+    let alias = match path.strip_suffix("index.html") {
+        Some(dir) if dir.ends_with('/') => dir.to_owned(),
+        Some(_) => return None,
+        None if path.ends_with('/') => format!("{}index.html", path),
+        None => return None,
+    };
+    let mut alias_url = url.clone();
+    alias_url.set_path(&alias);
+    Some(alias_url)
+}
+
+fn without_fragment(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+/// The path of the file at `url` within `SNAPSHOT_DIR`. Pages without file name are saved as
+/// `index.html` in a directory of their name, which is what file urls resolve them to.
+fn local_path(url: &Url, kind: FileKind) -> String {
+    let mut segments = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+                .map(|segment| {
+                    segment
+                        .chars()
+                        .map(|c| {
+                            if c.is_ascii_alphanumeric() || "._-".contains(c) {
+                                c
+                            } else {
+                                '_'
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let is_file = match segments.last() {
+        Some(last) if !url.path().ends_with('/') => {
+            kind != FileKind::Html || last.ends_with(".html") || last.ends_with(".htm")
+        }
+        _ => false,
+    };
+    if !is_file {
+        segments.push("index.html".to_owned());
+    }
+    segments.join("/")
+}
+
+/// `path`, numbered if it is taken by one of `files` or clashes with their directories
+fn unique_path(files: &[SnapshotFile], path: String) -> String {
+    let clashes = |path: &str| {
+        files.iter().any(|file| {
+            file.path == path
+                || file.path.starts_with(&format!("{}/", path))
+                || path.starts_with(&format!("{}/", file.path))
+        })
+    };
+    if !clashes(&path) {
+        return path;
+    }
+
+    // Number the directory taken by a file, or else the file name
+    let segments = path.split('/').collect::<Vec<_>>();
+    let numbered = (1..segments.len())
+        .find(|&n| {
+            files
+                .iter()
+                .any(|file| file.path == segments[..n].join("/"))
+        })
+        .map_or(segments.len() - 1, |n| n - 1);
+    let name = segments[numbered];
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| {
+            let mut segments = segments.clone();
+            let name = format!("{}-{}{}", stem, n, extension);
+            segments[numbered] = &name;
+            segments.join("/")
+        })
+        .find(|path| !clashes(path))
+        .unwrap()
+}
+
+/// The path of the file `to` relative to the directory of the file `from`
+fn relative_path(from: &str, to: &str) -> String {
+    let mut from_dirs = from.split('/').collect::<Vec<_>>();
+    from_dirs.pop();
+    let to = to.split('/').collect::<Vec<_>>();
+    let common = from_dirs
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts = vec![".."; from_dirs.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// The url pattern letting the webapp container navigate within the snapshot of an app in the
+/// directory `prefix`. The directory the package gets installed to is unknown when building.
+pub(super) fn snapshot_url_pattern(prefix: &str) -> String {
+    format!("file://*/{}{}/*", prefix, SNAPSHOT_DIR)
+}
+
+/// Urls without scheme are paths of the snapshot, relative to the package
+pub(super) fn is_local_url(url: &str) -> bool {
+    Url::parse(url).is_err()
+}
+
+/// The urls of the files of a snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct SnapshotManifest {
+    max_size: u64,
+    /// The site's urls by path within `SNAPSHOT_DIR`
+    files: BTreeMap<String, String>,
+}
+
+impl SnapshotManifest {
+    pub fn options(&self) -> OfflineSnapshot {
+        OfflineSnapshot {
+            max_size: self.max_size,
+        }
+    }
+
+    /// The site's url of the `local_url` of an app in the directory `prefix`
+    pub fn site_url(&self, prefix: &str, local_url: &str) -> Option<String> {
+        let path = local_url.strip_prefix(&format!("{}{}/", prefix, SNAPSHOT_DIR))?;
+        self.files.get(path).cloned()
+    }
+}
+
+/// Read the manifest of the snapshot of the app in the directory `prefix`
+pub(super) fn read_snapshot(
+    data: &HashMap<String, Vec<u8>>,
+    prefix: &str,
+) -> Result<SnapshotManifest, String> {
+    let path = format!("{}{}", prefix, SNAPSHOT_MANIFEST);
+    match data.get(&path) {
+        Some(manifest) => serde_json::from_slice(manifest)
+            .map_err(|err| format!("Invalid snapshot manifest: {}", err)),
+        None => Err(format!("Missing {}", path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use url::Url;
+
+    use super::super::{data_desktop_entry, split_exec, EmbeddedIcons, Package};
+    use super::super::{DesktopEntry, DESKTOP_ENTRY_GROUP};
+    use super::*;
+
+    fn snapshot_exec(package: &Package, prefix: &str) -> Vec<String> {
+        let snapshot = SiteSnapshot {
+            files: Vec::new(),
+            paths: HashMap::from([(Url::parse(&package.url).unwrap(), "index.html".to_owned())]),
+            warnings: Vec::new(),
+        };
+        let icons = EmbeddedIcons {
+            icon: "icon.svg".to_owned(),
+            symbolic_icon: None,
+            splash_image: None,
+            actions: Vec::new(),
+        };
+        let entry = data_desktop_entry(package, &icons, prefix, None, Some(&snapshot));
+        let entry = DesktopEntry::parse(&entry.to_string());
+        let exec = entry
+            .group(DESKTOP_ENTRY_GROUP)
+            .unwrap()
+            .get("Exec")
+            .unwrap();
+        split_exec(&exec).unwrap()
+    }
+
+    #[test]
+    fn snapshot_pages_match_the_url_patterns() {
+        let package = Package {
+            url: "https://example.com/".to_owned(),
+            name: "Site".to_owned(),
+            url_patterns: "https?://example.com/*".to_owned(),
+            ..Default::default()
+        };
+
+        let exec = snapshot_exec(&package, "");
+        assert_eq!(exec.last().unwrap(), "www/index.html");
+        assert!(
+            exec.contains(&"--webappUrlPatterns=https?://example.com/*,file://*/www/*".to_owned())
+        );
+
+        let exec = snapshot_exec(&package, "site/");
+        assert_eq!(exec.last().unwrap(), "site/www/index.html");
+        assert!(exec.contains(
+            &"--webappUrlPatterns=https?://example.com/*,file://*/site/www/*".to_owned()
+        ));
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn file(url: &str, kind: FileKind, path: &str) -> SnapshotFile {
+        SnapshotFile {
+            url: self::url(url),
+            kind,
+            data: Vec::new(),
+            path: path.to_owned(),
+        }
+    }
+
+    #[test]
+    fn finds_quoted_unquoted_and_srcset_references() {
+        let html = r#"<script src=app.js></script><img src="a.png" srcset="b.png 2x, c,d.png 3x">
+<video poster='poster.jpg'><a href=/about/>About</a>
+<img srcset=" e.png,f.png 100w ,  g.png (min-width: 10px, 1x)">
+<div style="background: url(bg.png)">"#;
+        let mut values = ReferencePatterns::new().values(FileKind::Html, html);
+        values.sort_unstable();
+        assert_eq!(
+            values,
+            [
+                "/about/",
+                "a.png",
+                "app.js",
+                "b.png",
+                "bg.png",
+                "c,d.png",
+                "e.png,f.png",
+                "g.png",
+                "poster.jpg"
+            ]
+        );
+
+        let css = r#"@import "base.css"; a { background: url( 'x.png' ) } b { mask: url(y.svg) }"#;
+        let values = ReferencePatterns::new().values(FileKind::Css, css);
+        assert_eq!(values, ["x.png", "y.svg", "base.css"]);
+        assert!(ReferencePatterns::new()
+            .values(FileKind::Other, html)
+            .is_empty());
+    }
+
+    #[test]
+    fn replaces_references() {
+        let html = r#"<img src=a.png srcset="a.png 1x, b.png 2x"><a href='#top'>"#;
+        let replaced =
+            ReferencePatterns::new().replace(FileKind::Html, html, |value| match value {
+                "a.png" => Some("img/a.png".to_owned()),
+                "b.png" => Some("https://example.com/b,2.png".to_owned()),
+                _ => None,
+            });
+        assert_eq!(
+            replaced,
+            r#"<img src=img/a.png srcset="img/a.png 1x, https://example.com/b%2C2.png 2x"><a href='#top'>"#
+        );
+
+        let regex = Regex::new(r#"url\((?:"([^"]*)"|([^)]*))\)"#).unwrap();
+        assert_eq!(
+            replace_references(r#"url("a") url(b)"#, &regex, |value| Some(value.repeat(2))),
+            r#"url("aa") url(bb)"#
+        );
+    }
+
+    #[test]
+    fn maps_urls_to_local_paths() {
+        let cases = [
+            ("https://example.com/", FileKind::Html, "index.html"),
+            (
+                "https://example.com/about",
+                FileKind::Html,
+                "about/index.html",
+            ),
+            (
+                "https://example.com/about/",
+                FileKind::Html,
+                "about/index.html",
+            ),
+            (
+                "https://example.com/a/page.html",
+                FileKind::Html,
+                "a/page.html",
+            ),
+            (
+                "https://example.com/css/main.css?v=2",
+                FileKind::Css,
+                "css/main.css",
+            ),
+            (
+                "https://example.com/../a b/%C3%A4.js",
+                FileKind::Other,
+                "a_20b/_C3_A4.js",
+            ),
+        ];
+        for (site_url, kind, path) in &cases {
+            assert_eq!(local_path(&url(site_url), *kind), *path, "{}", site_url);
+        }
+    }
+
+    #[test]
+    fn numbers_clashing_paths() {
+        let files = [
+            file("https://example.com/a", FileKind::Other, "a"),
+            file("https://example.com/b/", FileKind::Html, "b/index.html"),
+            file("https://example.com/c.js", FileKind::Other, "c.js"),
+        ];
+        assert_eq!(unique_path(&files, "d.js".to_owned()), "d.js");
+        assert_eq!(unique_path(&files, "c.js".to_owned()), "c-2.js");
+        // A file can't be stored where a directory is needed and vice versa
+        assert_eq!(
+            unique_path(&files, "a/index.html".to_owned()),
+            "a-2/index.html"
+        );
+        assert_eq!(unique_path(&files, "b".to_owned()), "b-2");
+    }
+
+    #[test]
+    fn relates_paths() {
+        assert_eq!(relative_path("index.html", "app.js"), "app.js");
+        assert_eq!(relative_path("index.html", "css/main.css"), "css/main.css");
+        assert_eq!(
+            relative_path("about/index.html", "index.html"),
+            "../index.html"
+        );
+        assert_eq!(relative_path("a/b/index.html", "a/c/x.png"), "../c/x.png");
+        assert_eq!(relative_path("a/index.html", "a/index.html"), "index.html");
+    }
+
+    #[test]
+    fn aliases_directories_and_their_index() {
+        assert_eq!(
+            index_alias(&url("https://example.com/dir/")),
+            Some(url("https://example.com/dir/index.html"))
+        );
+        assert_eq!(
+            index_alias(&url("https://example.com/dir/index.html?a=1")),
+            Some(url("https://example.com/dir/?a=1"))
+        );
+        assert_eq!(
+            index_alias(&url("https://example.com/dir/myindex.html")),
+            None
+        );
+        assert_eq!(index_alias(&url("https://example.com/dir")), None);
+    }
+
+    #[test]
+    fn rewrites_references_to_local_copies() {
+        let page = file(
+            "https://example.com/about/",
+            FileKind::Html,
+            "about/index.html",
+        );
+        let snapshot = SiteSnapshot {
+            files: Vec::new(),
+            paths: HashMap::from([
+                (url("https://example.com/"), "index.html".to_owned()),
+                (url("https://example.com/app.js"), "app.js".to_owned()),
+            ]),
+            warnings: Vec::new(),
+        };
+        let rewrite = |value: &str| snapshot.rewrite_reference(&page, value);
+
+        assert_eq!(rewrite("/").unwrap(), "../index.html");
+        assert_eq!(rewrite("../app.js#main").unwrap(), "../app.js#main");
+        assert_eq!(
+            rewrite("/search?q=a&amp;b=(1)").unwrap(),
+            "https://example.com/search?q=a&amp;b=%281%29"
+        );
+        assert_eq!(rewrite("#top"), None);
+        assert_eq!(rewrite("mailto:a@example.com"), None);
+    }
+}
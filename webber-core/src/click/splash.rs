@@ -0,0 +1,64 @@
+use super::{DesktopGroup, Icon, Package};
+
+/// The splash screen Lomiri shows while the app is starting. Empty values fall back to the
+/// corresponding settings of the package.
+#[derive(Debug, Default)]
+pub struct Splash {
+    /// The background color, defaults to the background color or theme color of the package
+    pub color: String,
+    /// The color of the header, defaults to the theme color of the package
+    pub header_color: String,
+    /// An image shown in the center of the splash screen
+    pub image: Option<Icon>,
+    /// The title shown in the header, defaults to the name of the package
+    pub title: String,
+    pub show_header: bool,
+}
+
+impl Splash {
+    /// Read the splash screen settings from the keys of a desktop entry. The image is left empty.
+    pub(super) fn from_desktop_entry(entry: &DesktopGroup) -> Self {
+        let get = |key: &str| entry.get(key).unwrap_or_default();
+        Self {
+            color: get("X-Lomiri-Splash-Color"),
+            header_color: get("X-Lomiri-Splash-Color-Header"),
+            image: None,
+            title: get("X-Lomiri-Splash-Title"),
+            show_header: get("X-Lomiri-Splash-Show-Header") == "true",
+        }
+    }
+}
+
+impl Package {
+    /// The `X-Lomiri-Splash-*` keys of the desktop entry
+    pub(super) fn splash_keys(&self, image_fname: Option<&str>) -> Vec<(&'static str, String)> {
+        let splash = &self.splash;
+        let or = |value: &str, default: &str| {
+            if value.is_empty() {
+                default.to_owned()
+            } else {
+                value.to_owned()
+            }
+        };
+
+        let mut keys = vec![(
+            "X-Lomiri-Splash-Color",
+            or(
+                &splash.color,
+                &or(&self.background_color, &self.theme_color),
+            ),
+        )];
+        if splash.show_header {
+            keys.push(("X-Lomiri-Splash-Show-Header", "true".to_owned()));
+            keys.push((
+                "X-Lomiri-Splash-Color-Header",
+                or(&splash.header_color, &self.theme_color),
+            ));
+            keys.push(("X-Lomiri-Splash-Title", or(&splash.title, &self.name)));
+        }
+        if let Some(image_fname) = image_fname {
+            keys.push(("X-Lomiri-Splash-Image", image_fname.to_owned()));
+        }
+        keys
+    }
+}
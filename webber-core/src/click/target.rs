@@ -0,0 +1,99 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{policy_groups, PolicyGroup};
+
+/// The Ubuntu Touch release a package is built for. Releases are distributed on different OTA
+/// channels and differ in their click framework, AppArmor policy and webapp container.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Ubuntu Touch 16.04, still running on legacy devices
+    Xenial,
+    /// Ubuntu Touch 20.04
+    #[default]
+    Focal,
+    /// Ubuntu Touch 24.04
+    Noble,
+}
+
+impl Target {
+    pub const ALL: &'static [Target] = &[Target::Xenial, Target::Focal, Target::Noble];
+
+    /// The Ubuntu version of the release, e.g. `20.04`
+    pub fn version(&self) -> &'static str {
+        match self {
+            Target::Xenial => "16.04",
+            Target::Focal => "20.04",
+            Target::Noble => "24.04",
+        }
+    }
+
+    /// The click framework packages for the release depend on
+    pub fn framework(&self) -> &'static str {
+        match self {
+            Target::Xenial => "ubuntu-sdk-16.04",
+            Target::Focal => "ubuntu-sdk-20.04",
+            Target::Noble => "ubuntu-touch-24.04-1.x",
+        }
+    }
+
+    /// The AppArmor policy version. 24.04 keeps using the 20.04 policy.
+    pub fn policy_version(&self) -> &'static str {
+        match self {
+            Target::Xenial => "16.04",
+            Target::Focal | Target::Noble => "20.04",
+        }
+    }
+
+    /// The policy groups available with the release's policy version
+    pub fn policy_groups(&self) -> Vec<PolicyGroup> {
+        policy_groups(self.policy_version()).unwrap_or_default()
+    }
+
+    /// Whether the webapp container plays audio through media-hub, which was dropped in 24.04
+    pub fn media_hub_audio(&self) -> bool {
+        match self {
+            Target::Xenial | Target::Focal => true,
+            Target::Noble => false,
+        }
+    }
+
+    /// The release whose packages depend on `framework`
+    pub fn from_framework(framework: &str) -> Option<Target> {
+        Target::ALL
+            .iter()
+            .copied()
+            .find(|target| target.framework() == framework)
+    }
+
+    /// The newest release using the AppArmor policy version `policy_version`
+    pub fn from_policy_version(policy_version: &str) -> Option<Target> {
+        Target::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|target| target.policy_version() == policy_version)
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "16.04" | "xenial" => Ok(Target::Xenial),
+            "20.04" | "focal" => Ok(Target::Focal),
+            "24.04" | "noble" => Ok(Target::Noble),
+            _ => Err(format!(
+                "Unknown target \"{}\", expected 16.04, 20.04 or 24.04",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.version())
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::{mkdir, write_file, Package};
+
+/// The directory of the webapp model, passed to the webapp container as model search path
+pub(super) const WEBAPP_MODEL_DIR: &str = "webapp";
+/// The directory of the shortcut's webapp within `WEBAPP_MODEL_DIR`
+const WEBAPP_DIR: &str = "shortcut";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserScriptKind {
+    /// JavaScript run on every page
+    Script,
+    /// CSS added to every page
+    Stylesheet,
+}
+
+impl UserScriptKind {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            UserScriptKind::Script => "js",
+            UserScriptKind::Stylesheet => "css",
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "js" => Some(UserScriptKind::Script),
+            "css" => Some(UserScriptKind::Stylesheet),
+            _ => None,
+        }
+    }
+}
+
+/// A script or stylesheet shipped with the shortcut and injected into the site
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserScript {
+    pub name: String,
+    pub kind: UserScriptKind,
+    pub source: String,
+}
+
+impl UserScript {
+    /// Load a `.js` or `.css` file, named after the file
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let kind = path
+            .extension()
+            .and_then(|extension| UserScriptKind::from_extension(&extension.to_string_lossy()))
+            .ok_or_else(|| format!("{} is neither a .js nor a .css file", path.display()))?;
+        Ok(Self {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            kind,
+            source: fs::read_to_string(path)?,
+        })
+    }
+}
+
+/// A built-in fix for sites that are unpleasant in the webapp container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tweak {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub kind: UserScriptKind,
+    pub source: &'static str,
+}
+
+impl Tweak {
+    /// The user script applying the tweak, named after its id
+    pub fn user_script(&self) -> UserScript {
+        UserScript {
+            name: self.id.to_owned(),
+            kind: self.kind,
+            source: self.source.to_owned(),
+        }
+    }
+}
+
+pub const TWEAKS: &[Tweak] = &[
+    Tweak {
+        id: "hide-app-banners",
+        description: "Hide banners asking to open the site in its app",
+        kind: UserScriptKind::Stylesheet,
+        source: "[class*=\"smartbanner\"], [id*=\"smartbanner\"], [class*=\"app-banner\"], \
+                 [id*=\"app-banner\"], [class*=\"open-in-app\"], [class*=\"OpenInApp\"] {\n    \
+                 display: none !important;\n}\n",
+    },
+    Tweak {
+        id: "dark-mode",
+        description: "Force a dark color scheme",
+        kind: UserScriptKind::Stylesheet,
+        source: ":root {\n    color-scheme: dark;\n    \
+                 filter: invert(1) hue-rotate(180deg);\n    background: #fff;\n}\n\
+                 img, picture, video, canvas, iframe, svg image {\n    \
+                 filter: invert(1) hue-rotate(180deg);\n}\n",
+    },
+    Tweak {
+        id: "larger-text",
+        description: "Enlarge the text",
+        kind: UserScriptKind::Stylesheet,
+        source: "html {\n    font-size: 120% !important;\n    \
+                 -webkit-text-size-adjust: 120% !important;\n}\n",
+    },
+    Tweak {
+        id: "no-pull-to-refresh",
+        description: "Disable reloading the page by pulling it down",
+        kind: UserScriptKind::Stylesheet,
+        source: "html, body {\n    overscroll-behavior-y: contain;\n}\n",
+    },
+];
+
+/// The built-in tweak `id`
+pub fn tweak(id: &str) -> Option<&'static Tweak> {
+    TWEAKS.iter().find(|tweak| tweak.id == id)
+}
+
+/// The manifest of an Ubuntu webapp, the format the webapp container loads user scripts from
+#[derive(Debug, Serialize, Deserialize)]
+struct WebappManifest {
+    name: String,
+    homepage: String,
+    includes: Vec<String>,
+    /// The scripts injected into every page, relative to the manifest
+    scripts: Vec<String>,
+    /// The user scripts as given, `scripts` only contains loaders for stylesheets
+    #[serde(rename = "x-webber-user-scripts", default)]
+    user_scripts: Vec<ManifestUserScript>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestUserScript {
+    name: String,
+    file: String,
+}
+
+/// The arguments of the webapp container loading the webapp model in `search_path`
+pub(super) fn webapp_args(package: &Package, search_path: &str) -> Vec<String> {
+    vec![
+        format!("--webappModelSearchPath={}", search_path),
+        format!(
+            "--webapp={}",
+            base64::engine::general_purpose::STANDARD.encode(&package.name)
+        ),
+    ]
+}
+
+/// Write the webapp model with the package's user scripts into `dir/WEBAPP_MODEL_DIR` and return
+/// the scripts injected into the pages, relative to `dir`
+pub(super) fn write_webapp_model(
+    package: &Package,
+    dir: &Path,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let app_dir = dir.join(WEBAPP_MODEL_DIR).join(WEBAPP_DIR);
+    mkdir(&dir.join(WEBAPP_MODEL_DIR))?;
+    mkdir(&app_dir)?;
+
+    let mut manifest = WebappManifest {
+        name: package.name.clone(),
+        homepage: package.url.clone(),
+        includes: package
+            .url_patterns
+            .split(',')
+            .filter(|pat| !pat.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        scripts: Vec::new(),
+        user_scripts: Vec::new(),
+    };
+    for (idx, script) in package.user_scripts.iter().enumerate() {
+        let file = format!("user-{}.{}", idx + 1, script.kind.extension());
+        write_file(&app_dir.join(&file), &script.source)?;
+        match script.kind {
+            UserScriptKind::Script => manifest.scripts.push(file.clone()),
+            UserScriptKind::Stylesheet => {
+                let loader = format!("{}.js", file);
+                write_file(&app_dir.join(&loader), &stylesheet_loader(&script.source)?)?;
+                manifest.scripts.push(loader);
+            }
+        }
+        manifest.user_scripts.push(ManifestUserScript {
+            name: script.name.clone(),
+            file,
+        });
+    }
+
+    write_file(
+        &app_dir.join("manifest.json"),
+        &serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(manifest
+        .scripts
+        .iter()
+        .map(|script| format!("{}/{}/{}", WEBAPP_MODEL_DIR, WEBAPP_DIR, script))
+        .collect())
+}
+
+/// A script adding `css` to the page. The stylesheet is embedded as JSON string literal, so it
+/// can't break out of the script.
+fn stylesheet_loader(css: &str) -> serde_json::Result<String> {
+    Ok(format!(
+        "(function () {{\n    \
+         var style = document.createElement(\"style\");\n    \
+         style.textContent = {};\n    \
+         (document.head || document.documentElement).appendChild(style);\n\
+         }})();\n",
+        serde_json::to_string(css)?
+    ))
+}
+
+/// Read the user scripts from the webapp model in `search_path`
+pub(super) fn read_webapp_model(
+    data: &HashMap<String, Vec<u8>>,
+    search_path: &str,
+) -> Result<Vec<UserScript>, String> {
+    let app_dir = format!("{}/{}", search_path, WEBAPP_DIR);
+    let manifest_path = format!("{}/manifest.json", app_dir);
+    let manifest: WebappManifest = match data.get(&manifest_path) {
+        Some(manifest) => serde_json::from_slice(manifest)
+            .map_err(|err| format!("Invalid webapp manifest: {}", err))?,
+        None => return Err(format!("Missing {}", manifest_path)),
+    };
+    if let Some(script) = manifest
+        .scripts
+        .iter()
+        .find(|script| !data.contains_key(&format!("{}/{}", app_dir, script)))
+    {
+        return Err(format!("Missing user script {}/{}", app_dir, script));
+    }
+
+    manifest
+        .user_scripts
+        .into_iter()
+        .map(|script| {
+            let path = format!("{}/{}", app_dir, script.file);
+            let kind = Path::new(&script.file)
+                .extension()
+                .and_then(|extension| UserScriptKind::from_extension(&extension.to_string_lossy()))
+                .ok_or_else(|| format!("Unknown kind of user script {}", path))?;
+            let source = data
+                .get(&path)
+                .ok_or_else(|| format!("Missing user script {}", path))?;
+            Ok(UserScript {
+                name: script.name,
+                kind,
+                source: String::from_utf8_lossy(source).into_owned(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,29 @@
+#![feature(generic_associated_types)]
+#![recursion_limit = "256"]
+
+//! Scraping of websites and web app manifests and creation of click packages for web app shortcuts.
+//!
+//! The QML models and image providers used by the Webber app are only available with the `qt`
+//! feature, which is enabled by default. Use `default-features = false` to depend on the scraping
+//! and packaging logic without a Qt toolchain.
+
+#[cfg(all(feature = "qt", not(no_qt)))]
+#[macro_use]
+extern crate qmetaobject;
+
+pub mod click;
+pub mod fetch_and_resolve;
+pub mod fetchable;
+pub mod pwa;
+pub mod resolvable;
+pub mod scraper;
+pub mod serde_utils;
+
+#[cfg(all(feature = "qt", not(no_qt)))]
+pub mod icon_model;
+#[cfg(all(feature = "qt", not(no_qt)))]
+pub mod icon_provider;
+#[cfg(all(feature = "qt", not(no_qt)))]
+pub mod model;
+#[cfg(all(feature = "qt", not(no_qt)))]
+pub mod qt;
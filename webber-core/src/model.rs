@@ -0,0 +1,804 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::path::PathBuf;
+
+use csscolorparser::Color;
+use url::Url;
+
+use qmetaobject::*;
+
+use crate::click;
+use crate::resolvable::Resolved;
+use crate::scraper::{self, ScrapedSite};
+
+/// A scraped site and the translations of its name
+type ScrapeResult = (ScrapedSite<Resolved>, Vec<click::LocalizedName>);
+
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct WebScraper {
+    base: qt_base_class!(trait QObject),
+    url: qt_property!(QString; NOTIFY urlChanged),
+    urlChanged: qt_signal!(),
+    /// Languages to look up the name in, separated by commas or spaces
+    languages: qt_property!(QString; NOTIFY languagesChanged),
+    languagesChanged: qt_signal!(),
+    siteName: qt_property!(QString; NOTIFY scraped),
+    title: qt_property!(QString; NOTIFY scraped),
+    /// Translations of the name as [locale, name, rtl] lists
+    names: qt_property!(QVariant; NOTIFY scraped),
+    themeColor: qt_property!(QString; NOTIFY scraped),
+    backgroundColor: qt_property!(QString; NOTIFY scraped),
+    iconUrl: qt_property!(QString; NOTIFY scraped),
+    icons: qt_property!(QVariant; NOTIFY scraped),
+    maskableIcons: qt_property!(QVariant; NOTIFY scraped),
+    monochromeIconUrl: qt_property!(QString; NOTIFY scraped),
+    splashIconUrl: qt_property!(QString; NOTIFY scraped),
+    orientation: qt_property!(QString; NOTIFY scraped),
+    display: qt_property!(QString; NOTIFY scraped),
+    defaultEnableAddressBar: qt_property!(bool; NOTIFY scraped),
+    defaultEnableBackForward: qt_property!(bool; NOTIFY scraped),
+    defaultEnableFullscreen: qt_property!(bool; NOTIFY scraped),
+    shortcuts: qt_property!(QVariant; NOTIFY scraped),
+    defaultUrlPatterns: qt_property!(QVariant; NOTIFY scraped),
+    scraped: qt_signal!(),
+    busy: qt_property!(bool; NOTIFY busyChanged),
+    busyChanged: qt_signal!(),
+    errorString: qt_property!(QString; NOTIFY errorStringChanged),
+    errorStringChanged: qt_signal!(),
+    scrape: qt_method!(fn(&mut self)),
+    scrape_mutex: std::sync::Arc<std::sync::Mutex<()>>,
+}
+
+impl WebScraper {
+    fn scrape(&mut self) {
+        let url = self.url.to_string();
+
+        match scraper::validate_url(url) {
+            Ok(url) => {
+                self.errorString = QString::default();
+                self.errorStringChanged();
+                self.run_scrape_thread(url);
+            }
+            Err(s) => {
+                let msg = format!("Invalid url: {}", s);
+                let msg = QString::from(msg);
+                self.errorString = msg;
+                self.errorStringChanged();
+            }
+        }
+    }
+
+    fn run_scrape_thread(&mut self, url: url::Url) {
+        self.busy = true;
+        self.busyChanged();
+
+        let qptr = QPointer::from(&*self);
+        let set_error_string = qmetaobject::queued_callback(move |val: QString| {
+            if let Some(self_) = qptr.as_pinned() {
+                self_.borrow_mut().errorString = val;
+                self_.borrow().errorStringChanged();
+            }
+        });
+        let qptr = QPointer::from(&*self);
+        let set_scrape_result = qmetaobject::queued_callback(move |(res, names): ScrapeResult| {
+            if let Some(self_) = qptr.as_pinned() {
+                let preferred_icon = res.icons.first();
+                let white = Color::from_rgb_u8(255, 255, 255);
+
+                self_.borrow_mut().title = QString::from(res.title.unwrap_or_default());
+                self_.borrow_mut().siteName = QString::from(res.site_name.unwrap_or_default());
+
+                let mut list = QVariantList::default();
+                for name in names {
+                    let mut item = QVariantList::default();
+                    item.push(QVariant::from(QString::from(name.locale)));
+                    item.push(QVariant::from(QString::from(name.name)));
+                    item.push(QVariant::from(name.rtl));
+                    list.push(QVariant::from(item));
+                }
+                self_.borrow_mut().names = QVariant::from(list);
+
+                self_.borrow_mut().themeColor =
+                    QString::from(res.theme_color.unwrap_or(white).to_hex_string());
+                self_.borrow_mut().backgroundColor = QString::from(
+                    res.background_color
+                        .map(|color| color.to_hex_string())
+                        .unwrap_or_default(),
+                );
+                self_.borrow_mut().iconUrl =
+                    QString::from(preferred_icon.map(Url::as_str).unwrap_or_default());
+                self_.borrow_mut().monochromeIconUrl = QString::from(
+                    res.monochrome_icons
+                        .first()
+                        .map(Url::as_str)
+                        .unwrap_or_default(),
+                );
+                self_.borrow_mut().splashIconUrl = QString::from(
+                    res.splash_icon
+                        .as_ref()
+                        .map(Url::as_str)
+                        .unwrap_or_default(),
+                );
+
+                self_.borrow_mut().orientation = QString::from(
+                    res.orientation
+                        .map(|orientation| orientation.keyword())
+                        .unwrap_or_default(),
+                );
+
+                self_.borrow_mut().display = QString::from(
+                    res.display
+                        .map(|display| display.keyword())
+                        .unwrap_or_default(),
+                );
+                let flags = res
+                    .display
+                    .map(click::DisplayFlags::from)
+                    .unwrap_or_default();
+                self_.borrow_mut().defaultEnableAddressBar = flags.enable_address_bar;
+                self_.borrow_mut().defaultEnableBackForward = flags.enable_back_forward;
+                self_.borrow_mut().defaultEnableFullscreen = flags.enable_fullscreen;
+
+                let mut shortcuts = QVariantList::default();
+                for shortcut in res.shortcuts {
+                    let action = click::Action::from(shortcut);
+                    let icon_url = match action.icon {
+                        Some(click::Icon::Remote(url)) => url,
+                        _ => String::new(),
+                    };
+                    let mut item = QVariantList::default();
+                    item.push(QVariant::from(QString::from(action.name)));
+                    item.push(QVariant::from(QString::from(action.url)));
+                    item.push(QVariant::from(QString::from(icon_url)));
+                    shortcuts.push(QVariant::from(item));
+                }
+                self_.borrow_mut().shortcuts = QVariant::from(shortcuts);
+
+                let mut icons = QVariantList::default();
+                for icon in res.icons {
+                    icons.push(QVariant::from(QString::from(icon.as_str())));
+                }
+                self_.borrow_mut().icons = QVariant::from(icons);
+
+                let mut maskable_icons = QVariantList::default();
+                for icon in res.maskable_icons {
+                    maskable_icons.push(QVariant::from(QString::from(icon.as_str())));
+                }
+                self_.borrow_mut().maskableIcons = QVariant::from(maskable_icons);
+
+                let mut list = QVariantList::default();
+                for pat in res.default_url_patterns {
+                    list.push(QVariant::from(QString::from(pat)));
+                }
+                self_.borrow_mut().defaultUrlPatterns = QVariant::from(list);
+                self_.borrow().scraped();
+            }
+        });
+        let qptr = QPointer::from(&*self);
+        let set_busy = qmetaobject::queued_callback(move |busy| {
+            if let Some(self_) = qptr.as_pinned() {
+                self_.borrow_mut().busy = busy;
+                self_.borrow().busyChanged();
+            }
+        });
+
+        let mutex = self.scrape_mutex.clone();
+        let languages = self
+            .languages
+            .to_string()
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|lang| !lang.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        std::thread::spawn(move || {
+            if let Ok(lock) = mutex.try_lock() {
+                match scraper::scrape(&url) {
+                    Ok(res) => {
+                        let mut names = Vec::<click::LocalizedName>::new();
+                        for (lang, name, rtl) in scraper::scrape_names(&url, &res, &languages) {
+                            // Names with language tags that aren't valid locales are skipped
+                            if let Ok(name) = click::LocalizedName::new(&lang, name, rtl) {
+                                if !names.iter().any(|other| other.locale == name.locale) {
+                                    names.push(name);
+                                }
+                            }
+                        }
+                        set_scrape_result((res, names))
+                    }
+                    Err(err) => {
+                        let msg = format!("Failed to load site: {}", err);
+                        set_error_string(QString::from(msg));
+                    }
+                };
+                drop(lock)
+            }
+            set_busy(false);
+        });
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct AppModel {
+    base: qt_base_class!(trait QObject),
+    create: qt_method!(fn(&mut self)),
+    created: qt_signal!(),
+    urlPatterns: qt_property!(RefCell<UrlPatterns>; CONST),
+    permissions: qt_property!(RefCell<Permissions>; CONST),
+    tweaks: qt_property!(RefCell<Tweaks>; CONST),
+    shortcuts: qt_property!(RefCell<Shortcuts>; CONST),
+    translations: qt_property!(RefCell<Translations>; CONST),
+    url: qt_property!(String),
+    name: qt_property!(String),
+    themeColor: qt_property!(String),
+    backgroundColor: qt_property!(String),
+    iconUrl: qt_property!(String),
+    maskableIcon: qt_property!(bool),
+    monochromeIconUrl: qt_property!(String),
+    splashColor: qt_property!(String),
+    splashHeaderColor: qt_property!(String),
+    splashImageUrl: qt_property!(String),
+    splashTitle: qt_property!(String),
+    splashShowHeader: qt_property!(bool),
+    /// A manifest orientation keyword, or empty to allow any orientation
+    orientation: qt_property!(String),
+    enableAddressBar: qt_property!(bool),
+    enableBackForward: qt_property!(bool),
+    enableFullscreen: qt_property!(bool),
+    userAgent: qt_property!(String),
+    /// The Ubuntu Touch release to build for, e.g. "20.04"
+    target: qt_property!(String),
+    /// Whether to create a store package with the following metadata instead of a shortcut
+    publish: qt_property!(bool),
+    maintainer: qt_property!(String),
+    description: qt_property!(String),
+    namespace: qt_property!(String),
+    license: qt_property!(String),
+    /// Options of the webapp container, see `click::ContainerOptions`. The runtime is
+    /// "webapp-container" or "qml".
+    runtime: qt_property!(String),
+    pullToRefresh: qt_property!(bool),
+    storeSessionCookies: qt_property!(bool),
+    mediaHubAudio: qt_property!(bool),
+    /// "browser" or "overlay"
+    externalLinks: qt_property!(String),
+    localContentRemoteAccess: qt_property!(bool),
+    maximized: qt_property!(bool),
+    /// The port of the web inspector, 0 to disable it
+    inspectorPort: qt_property!(i32),
+    /// Ship a snapshot of the site for offline use, see `click::OfflineSnapshot`
+    offline: qt_property!(bool),
+    clickPath: qt_property!(String; NOTIFY clickPathChanged),
+    clickPathChanged: qt_signal!(),
+    lintMessages: qt_property!(QVariant; NOTIFY lintMessagesChanged),
+    hasLintErrors: qt_property!(bool; NOTIFY lintMessagesChanged),
+    lintMessagesChanged: qt_signal!(),
+    screenshotIconPath: qt_property!(String; READ screenshot_icon_path),
+    customIconPath: qt_property!(String),
+    useScreenshotIcon: qt_property!(bool),
+    useCustomIcon: qt_property!(bool),
+}
+
+impl AppModel {
+    fn create(&mut self) {
+        let names = self.translations.borrow().get_names();
+        let mut package = click::Package {
+            url: self.url.clone(),
+            name: self.name.clone(),
+            names: names.clone().unwrap_or_default(),
+            icon: if self.useCustomIcon {
+                click::Icon::Local(self.customIconPath.clone())
+            } else if self.useScreenshotIcon {
+                click::Icon::Local(self.screenshot_icon_path())
+            } else {
+                click::Icon::Remote(self.iconUrl.clone())
+            },
+            maskable_icon: self.maskableIcon && !self.useCustomIcon && !self.useScreenshotIcon,
+            monochrome_icon: if self.monochromeIconUrl.is_empty() {
+                None
+            } else {
+                Some(click::Icon::Remote(self.monochromeIconUrl.clone()))
+            },
+            splash: click::Splash {
+                color: self.splashColor.clone(),
+                header_color: self.splashHeaderColor.clone(),
+                image: if self.splashImageUrl.is_empty() {
+                    None
+                } else {
+                    Some(click::Icon::Remote(self.splashImageUrl.clone()))
+                },
+                title: self.splashTitle.clone(),
+                show_header: self.splashShowHeader,
+            },
+            orientation: self.orientation.parse().ok(),
+            actions: self.shortcuts.borrow().get_enabled(),
+            theme_color: self.themeColor.clone(),
+            background_color: self.backgroundColor.clone(),
+            url_patterns: self.urlPatterns.borrow().get_patterns_string(),
+            permissions: self.permissions.borrow().get_enabled(),
+            policy_template: click::PolicyTemplate::default(),
+            read_paths: Vec::new(),
+            write_paths: Vec::new(),
+            enable_address_bar: self.enableAddressBar,
+            enable_back_forward: self.enableBackForward,
+            enable_fullscreen: self.enableFullscreen,
+            user_agent: self.userAgent.clone(),
+            container: click::ContainerOptions {
+                runtime: self.runtime.parse().unwrap_or_default(),
+                pull_to_refresh: self.pullToRefresh,
+                store_session_cookies: self.storeSessionCookies,
+                media_hub_audio: self.mediaHubAudio,
+                external_links: self.externalLinks.parse().unwrap_or_default(),
+                local_content_remote_access: self.localContentRemoteAccess,
+                maximized: self.maximized,
+                inspector_port: u16::try_from(self.inspectorPort)
+                    .ok()
+                    .filter(|&port| port > 0),
+            },
+            user_scripts: self.tweaks.borrow().get_enabled(),
+            offline_snapshot: if self.offline {
+                Some(click::OfflineSnapshot::default())
+            } else {
+                None
+            },
+            target: self.target.parse().unwrap_or_default(),
+            version: click::INITIAL_VERSION.to_owned(),
+            reproducible: false,
+            publishing: if self.publish {
+                Some(click::Publishing {
+                    maintainer: self.maintainer.trim().to_owned(),
+                    description: if self.description.trim().is_empty() {
+                        self.name.trim().to_owned()
+                    } else {
+                        self.description.trim().to_owned()
+                    },
+                    namespace: self.namespace.trim().to_owned(),
+                    license: self.license.trim().to_owned(),
+                })
+            } else {
+                None
+            },
+        };
+        package.sanitize();
+
+        let qptr = QPointer::from(&*self);
+        let set_created = qmetaobject::queued_callback(
+            move |(path, messages): (PathBuf, Vec<click::LintMessage>)| {
+                if let Some(self_) = qptr.as_pinned() {
+                    let mut list = QVariantList::default();
+                    for msg in &messages {
+                        list.push(QVariant::from(QString::from(msg.to_string())));
+                    }
+                    self_.borrow_mut().lintMessages = QVariant::from(list);
+                    self_.borrow_mut().hasLintErrors = messages
+                        .iter()
+                        .any(|msg| msg.severity == click::Severity::Error);
+                    self_.borrow().lintMessagesChanged();
+
+                    self_.borrow_mut().clickPath = path.to_str().unwrap().to_owned();
+                    self_.borrow_mut().clickPathChanged();
+                    self_.borrow().created();
+                }
+            },
+        );
+
+        std::thread::spawn(move || {
+            if let Err(errors) = names {
+                set_created((PathBuf::new(), errors));
+                return;
+            }
+            let history = match click::BuildHistory::new().and_then(|history| {
+                package.version = history.next_version(&package)?;
+                Ok(history)
+            }) {
+                Ok(history) => history,
+                Err(err) => {
+                    let message = click::LintMessage {
+                        severity: click::Severity::Error,
+                        file: None,
+                        message: err.to_string(),
+                    };
+                    set_created((PathBuf::new(), vec![message]));
+                    return;
+                }
+            };
+            let (path, mut messages) = match package.create() {
+                Ok(created) => created,
+                Err(err) => {
+                    let message = click::LintMessage {
+                        severity: click::Severity::Error,
+                        file: None,
+                        message: err.to_string(),
+                    };
+                    set_created((PathBuf::new(), vec![message]));
+                    return;
+                }
+            };
+            messages.extend(click::lint(&path).unwrap_or_else(|err| {
+                vec![click::LintMessage {
+                    severity: click::Severity::Error,
+                    file: None,
+                    message: err.to_string(),
+                }]
+            }));
+            if let Err(err) = history.record(&package, &path) {
+                messages.push(click::LintMessage {
+                    severity: click::Severity::Warning,
+                    file: None,
+                    message: format!(
+                        "Failed to record the build, the next build can't be installed as \
+                         upgrade: {}",
+                        err
+                    ),
+                });
+            }
+            set_created((path, messages));
+        });
+    }
+
+    fn screenshot_icon_path(&self) -> String {
+        let path = xdg::BaseDirectories::new()
+            .unwrap()
+            .get_cache_home()
+            .join("webber.timsueberkrueb");
+        path.join("screenshot-icon.png")
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+}
+
+#[derive(Default, Clone, SimpleListItem)]
+pub struct UrlPattern {
+    pub url: String,
+}
+
+pub type UrlPatternsModel = SimpleListModel<UrlPattern>;
+
+#[allow(non_snake_case)]
+#[derive(Default, QObject)]
+pub struct UrlPatterns {
+    base: qt_base_class!(trait QObject),
+    model: qt_property!(RefCell<UrlPatternsModel>; CONST),
+    setUrl: qt_method!(fn(&mut self, idx: usize, url: String) -> bool),
+    add: qt_method!(fn(&mut self, url: String)),
+    remove: qt_method!(fn(&mut self, index: usize) -> bool),
+    clear: qt_method!(fn(&mut self)),
+}
+
+impl UrlPatterns {
+    #[allow(non_snake_case)]
+    fn setUrl(&mut self, row: usize, url: String) -> bool {
+        let mut model = self.model.borrow_mut();
+
+        if row > model.row_count() as usize {
+            return false;
+        }
+        model.change_line(row, UrlPattern { url });
+        true
+    }
+
+    fn add(&mut self, url: String) {
+        let mut model = self.model.borrow_mut();
+        model.push(UrlPattern { url });
+    }
+
+    fn remove(&mut self, row: usize) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row > model.row_count() as usize {
+            return false;
+        }
+        model.remove(row);
+        true
+    }
+
+    fn clear(&mut self) {
+        let mut model = self.model.borrow_mut();
+        model.reset_data(Vec::default());
+    }
+
+    fn get_patterns_string(&self) -> String {
+        self.model
+            .borrow()
+            .iter()
+            .map(|pat| pat.url.clone())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Default, Clone, SimpleListItem)]
+pub struct Permission {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+impl Permission {
+    fn new(name: String, description: String, enabled: bool) -> Self {
+        Self {
+            name,
+            description,
+            enabled,
+        }
+    }
+}
+
+pub type PermissionsModel = SimpleListModel<Permission>;
+
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct Permissions {
+    base: qt_base_class!(trait QObject),
+    model: qt_property!(RefCell<PermissionsModel>; CONST),
+    add: qt_method!(fn(&mut self, name: String, description: String, default: bool)),
+    loadDefaults: qt_method!(fn(&mut self, target: String)),
+    clear: qt_method!(fn(&mut self)),
+    setEnabled: qt_method!(fn(&mut self, row: usize, enabled: bool) -> bool),
+}
+
+impl Permissions {
+    fn get_enabled(&self) -> Vec<String> {
+        self.model
+            .borrow()
+            .iter()
+            .filter_map(|perm| {
+                if perm.enabled {
+                    Some(perm.name.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn add(&mut self, name: String, description: String, default: bool) {
+        self.model
+            .borrow_mut()
+            .push(Permission::new(name, description, default));
+    }
+
+    /// Offer the policy groups the webapp container makes use of on `target`. Groups offered
+    /// before keep their state, new groups are enabled if requested by default.
+    #[allow(non_snake_case)]
+    pub fn loadDefaults(&mut self, target: String) {
+        let target: click::Target = target.parse().unwrap_or_default();
+        let mut model = self.model.borrow_mut();
+        let permissions = target
+            .policy_groups()
+            .iter()
+            .filter(|group| group.webapp)
+            .map(|group| {
+                let enabled = model
+                    .iter()
+                    .find(|perm| perm.name == group.name)
+                    .map_or(group.default, |perm| perm.enabled);
+                Permission::new(group.name.to_owned(), group.description.to_owned(), enabled)
+            })
+            .collect();
+        model.reset_data(permissions);
+    }
+
+    pub fn clear(&mut self) {
+        self.model.borrow_mut().reset_data(Vec::new());
+    }
+
+    #[allow(non_snake_case)]
+    fn setEnabled(&mut self, row: usize, enabled: bool) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row > model.row_count() as usize {
+            return false;
+        }
+        let mut perm = model[row].clone();
+        perm.enabled = enabled;
+        model.change_line(row, perm);
+        true
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Default, Clone, SimpleListItem)]
+pub struct Tweak {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+pub type TweaksModel = SimpleListModel<Tweak>;
+
+/// The built-in tweaks, see `click::TWEAKS`
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct Tweaks {
+    base: qt_base_class!(trait QObject),
+    model: qt_property!(RefCell<TweaksModel>; CONST),
+    loadDefaults: qt_method!(fn(&mut self)),
+    setEnabled: qt_method!(fn(&mut self, row: usize, enabled: bool) -> bool),
+}
+
+impl Tweaks {
+    fn get_enabled(&self) -> Vec<click::UserScript> {
+        self.model
+            .borrow()
+            .iter()
+            .filter(|tweak| tweak.enabled)
+            .filter_map(|tweak| click::tweak(&tweak.name))
+            .map(click::Tweak::user_script)
+            .collect()
+    }
+
+    /// Offer all built-in tweaks, disabled
+    #[allow(non_snake_case)]
+    pub fn loadDefaults(&mut self) {
+        let tweaks = click::TWEAKS
+            .iter()
+            .map(|tweak| Tweak {
+                name: tweak.id.to_owned(),
+                description: tweak.description.to_owned(),
+                enabled: false,
+            })
+            .collect();
+        self.model.borrow_mut().reset_data(tweaks);
+    }
+
+    #[allow(non_snake_case)]
+    fn setEnabled(&mut self, row: usize, enabled: bool) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row >= model.row_count() as usize {
+            return false;
+        }
+        let mut tweak = model[row].clone();
+        tweak.enabled = enabled;
+        model.change_line(row, tweak);
+        true
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Default, Clone, SimpleListItem)]
+pub struct Shortcut {
+    pub name: String,
+    pub url: String,
+    pub iconUrl: String,
+    pub enabled: bool,
+}
+
+pub type ShortcutsModel = SimpleListModel<Shortcut>;
+
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct Shortcuts {
+    base: qt_base_class!(trait QObject),
+    model: qt_property!(RefCell<ShortcutsModel>; CONST),
+    add: qt_method!(fn(&mut self, name: String, url: String, iconUrl: String)),
+    clear: qt_method!(fn(&mut self)),
+    setEnabled: qt_method!(fn(&mut self, row: usize, enabled: bool) -> bool),
+}
+
+impl Shortcuts {
+    fn get_enabled(&self) -> Vec<click::Action> {
+        self.model
+            .borrow()
+            .iter()
+            .filter(|shortcut| shortcut.enabled)
+            .map(|shortcut| click::Action {
+                name: shortcut.name.clone(),
+                url: shortcut.url.clone(),
+                icon: if shortcut.iconUrl.is_empty() {
+                    None
+                } else {
+                    Some(click::Icon::Remote(shortcut.iconUrl.clone()))
+                },
+            })
+            .collect()
+    }
+
+    #[allow(non_snake_case)]
+    pub fn add(&mut self, name: String, url: String, iconUrl: String) {
+        self.model.borrow_mut().push(Shortcut {
+            name,
+            url,
+            iconUrl,
+            enabled: true,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.model.borrow_mut().reset_data(Vec::new());
+    }
+
+    #[allow(non_snake_case)]
+    fn setEnabled(&mut self, row: usize, enabled: bool) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row >= model.row_count() as usize {
+            return false;
+        }
+        let mut shortcut = model[row].clone();
+        shortcut.enabled = enabled;
+        model.change_line(row, shortcut);
+        true
+    }
+}
+
+#[derive(Default, Clone, SimpleListItem)]
+pub struct Translation {
+    pub locale: String,
+    pub name: String,
+    pub rtl: bool,
+}
+
+pub type TranslationsModel = SimpleListModel<Translation>;
+
+#[allow(non_snake_case)]
+#[derive(QObject, Default)]
+pub struct Translations {
+    base: qt_base_class!(trait QObject),
+    model: qt_property!(RefCell<TranslationsModel>; CONST),
+    add: qt_method!(fn(&mut self, locale: String, name: String, rtl: bool)),
+    set: qt_method!(fn(&mut self, row: usize, locale: String, name: String) -> bool),
+    remove: qt_method!(fn(&mut self, row: usize) -> bool),
+    clear: qt_method!(fn(&mut self)),
+}
+
+impl Translations {
+    /// The translations, or an error for every translation with an invalid locale
+    fn get_names(&self) -> Result<Vec<click::LocalizedName>, Vec<click::LintMessage>> {
+        let mut names = Vec::new();
+        let mut errors = Vec::new();
+        for translation in self.model.borrow().iter() {
+            match click::LocalizedName::new(
+                &translation.locale,
+                translation.name.clone(),
+                Some(translation.rtl),
+            ) {
+                Ok(name) => names.push(name),
+                Err(err) => errors.push(click::LintMessage {
+                    severity: click::Severity::Error,
+                    file: None,
+                    message: format!("Translation \"{}\": {}", translation.name, err),
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(names)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn add(&mut self, locale: String, name: String, rtl: bool) {
+        self.model
+            .borrow_mut()
+            .push(Translation { locale, name, rtl });
+    }
+
+    fn set(&mut self, row: usize, locale: String, name: String) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row >= model.row_count() as usize {
+            return false;
+        }
+        // Keep the scraped direction unless the language changed
+        let rtl = if model[row].locale == locale {
+            model[row].rtl
+        } else {
+            click::is_rtl_language(&locale)
+        };
+        model.change_line(row, Translation { locale, name, rtl });
+        true
+    }
+
+    fn remove(&mut self, row: usize) -> bool {
+        let mut model = self.model.borrow_mut();
+        if row >= model.row_count() as usize {
+            return false;
+        }
+        model.remove(row);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.model.borrow_mut().reset_data(Vec::new());
+    }
+}
@@ -18,8 +18,19 @@ impl Fetchable for Manifest<Unresolved> {
     type Error = FetchManifestError;
 
     fn fetch(url: &Url) -> Result<Manifest<Unresolved>, FetchManifestError> {
+        Manifest::fetch_localized(url, None)
+    }
+}
+
+impl Manifest<Unresolved> {
+    /// Fetch the manifest at `url`, sending `language` as `Accept-Language` header. Sites serving
+    /// localized manifests pick the language from this header.
+    pub fn fetch_localized(
+        url: &Url,
+        language: Option<&str>,
+    ) -> Result<Manifest<Unresolved>, FetchManifestError> {
         let ManifestContent(s) =
-            download_manifest(url).map_err(FetchManifestError::NetworkError)?;
+            download_manifest(url, language).map_err(FetchManifestError::NetworkError)?;
 
         Manifest::parse(&s)
             .map_err(|e| e.to_string())
@@ -0,0 +1,23 @@
+use url::Url;
+
+use ::reqwest::header::ACCEPT_LANGUAGE;
+use reqwest::blocking as reqwest;
+
+pub(super) struct ManifestContent(pub String);
+
+/// Download the manifest at `url`, asking for the manifest in `language` if given
+pub(super) fn download_manifest(
+    url: &Url,
+    language: Option<&str>,
+) -> Result<ManifestContent, String> {
+    let mut request = reqwest::Client::new().get(url.as_ref());
+    if let Some(language) = language {
+        request = request.header(ACCEPT_LANGUAGE, language);
+    }
+    request
+        .send()
+        .map_err(|err| err.to_string())?
+        .text()
+        .map_err(|err| err.to_string())
+        .map(ManifestContent)
+}
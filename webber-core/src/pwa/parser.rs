@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 
 use serde::de::Visitor;
 use serde::{Deserialize, Deserializer};
@@ -36,6 +37,12 @@ pub struct Manifest<R: ResolveType> {
     #[serde(default)]
     #[serde(deserialize_with = "ok_or_none")]
     pub display: Option<DisplayMode>,
+    /// The manifest's display_override member is a list of display modes the developer prefers
+    /// over the display member, in order of preference. Display modes that aren't supported by
+    /// the user agent are skipped.
+    #[serde(default)]
+    #[serde(deserialize_with = "ok_or_none")]
+    pub display_override: Option<R::Array<DisplayMode>>,
     /// The manifest's icons member are images that serve as iconic representations of the web
     /// application in various contexts. For example, they can be used to represent the web
     /// application amongst a list of other applications, or to integrate the web application with
@@ -96,6 +103,17 @@ impl Manifest<Unresolved> {
     }
 }
 
+impl Manifest<Resolved> {
+    /// The display mode to use, taking `display_override` into account. Unknown display modes
+    /// were already dropped while parsing, so the first remaining override wins.
+    pub fn effective_display(&self) -> Option<DisplayMode> {
+        self.display_override
+            .as_ref()
+            .and_then(|modes| modes.first().copied())
+            .or(self.display)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[non_exhaustive]
 pub struct ShortcutItem<R: ResolveType> {
@@ -124,7 +142,7 @@ pub struct ShortcutItem<R: ResolveType> {
     pub icons: Option<R::Array<Icon<R::This>>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[non_exhaustive]
 pub enum Orientation {
     /// Any is an orientation that means the screen can be locked to any one of portrait-primary,
@@ -173,6 +191,45 @@ pub enum Orientation {
     LandscapeSecondary,
 }
 
+impl Orientation {
+    pub const ALL: &'static [Orientation] = &[
+        Orientation::Any,
+        Orientation::Natural,
+        Orientation::Landscape,
+        Orientation::Portrait,
+        Orientation::PortraitPrimary,
+        Orientation::PortraitSecondary,
+        Orientation::LandscapePrimary,
+        Orientation::LandscapeSecondary,
+    ];
+
+    /// The keyword of the orientation as used in manifests
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Orientation::Any => "any",
+            Orientation::Natural => "natural",
+            Orientation::Landscape => "landscape",
+            Orientation::Portrait => "portrait",
+            Orientation::PortraitPrimary => "portrait-primary",
+            Orientation::PortraitSecondary => "portrait-secondary",
+            Orientation::LandscapePrimary => "landscape-primary",
+            Orientation::LandscapeSecondary => "landscape-secondary",
+        }
+    }
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Orientation::ALL
+            .iter()
+            .find(|orientation| orientation.keyword() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown orientation \"{}\"", s))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[non_exhaustive]
 pub struct Icon<R: ResolveType> {
@@ -322,7 +379,7 @@ impl<'de> Deserialize<'de> for IconPurpose {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[non_exhaustive]
 pub enum TextDirection {
     /// Left-to-right text.
@@ -340,7 +397,7 @@ pub enum TextDirection {
 /// OS (e.g., in fullscreen, etc.). Display modes correspond to user interface (UI) metaphors and
 /// functionality in use on a given platform. The UI conventions of the display modes are purely
 /// advisory and implementers are free to interpret them how they best see fit.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[non_exhaustive]
 pub enum DisplayMode {
     /// Opens the web application with browser UI elements hidden and takes up the entirety of the
@@ -366,3 +423,34 @@ pub enum DisplayMode {
     #[serde(rename = "browser")]
     Browser,
 }
+
+impl DisplayMode {
+    pub const ALL: &'static [DisplayMode] = &[
+        DisplayMode::Fullscreen,
+        DisplayMode::Standalone,
+        DisplayMode::MinimalUI,
+        DisplayMode::Browser,
+    ];
+
+    /// The keyword of the display mode as used in manifests
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            DisplayMode::Fullscreen => "fullscreen",
+            DisplayMode::Standalone => "standalone",
+            DisplayMode::MinimalUI => "minimal-ui",
+            DisplayMode::Browser => "browser",
+        }
+    }
+}
+
+impl FromStr for DisplayMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        DisplayMode::ALL
+            .iter()
+            .find(|mode| mode.keyword() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown display mode \"{}\"", s))
+    }
+}
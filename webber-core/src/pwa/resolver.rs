@@ -12,6 +12,7 @@ impl Resolvable for Manifest<Unresolved> {
             background_color: self.background_color,
             dir: self.dir,
             display: self.display,
+            display_override: self.display_override.resolve(),
             icons: self.icons.map(|icons| {
                 Resolvable::resolve(icons, base_url)
                     .into_iter()
@@ -0,0 +1,19 @@
+pub mod image_provider;
+
+use cpp::*;
+
+cpp! {{
+    #include <QtCore/QCoreApplication>
+    #include <QtCore/QString>
+    #include <QtWebEngine/QtWebEngine>
+}}
+
+/// Initialize QtWebEngine and set up the application. Has to be called before the `QmlEngine` is
+/// created.
+pub fn init_application() {
+    cpp!(unsafe [] {
+        QtWebEngine::initialize();
+        QCoreApplication::setAttribute(Qt::AA_ShareOpenGLContexts);
+        QCoreApplication::setApplicationName(QStringLiteral("webber.timsueberkrueb"));
+    });
+}
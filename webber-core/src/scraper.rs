@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::string::ToString;
+
+use csscolorparser::Color;
+use url::Url;
+
+use crate::fetch_and_resolve::*;
+use crate::pwa;
+use crate::serde_utils::LossyVec;
+
+/// Icons smaller than this look blurry on splash screens
+const SPLASH_ICON_MIN_SIZE: u64 = 192;
+
+pub struct ScrapedSite<R: ResolveType> {
+    pub site_name: Option<String>,
+    pub title: Option<String>,
+    /// The language tag of the names, e.g. `pt-BR`
+    pub lang: Option<String>,
+    /// The base direction of the names
+    pub dir: Option<pwa::TextDirection>,
+    pub theme_color: Option<Color>,
+    pub background_color: Option<Color>,
+    /// Icons suitable for any purpose, including maskable icons
+    pub icons: R::Array<R::Url>,
+    /// Icons designed with a safe zone, which can be cropped
+    pub maskable_icons: R::Array<R::Url>,
+    /// Icons where only the alpha channel is meaningful
+    pub monochrome_icons: R::Array<R::Url>,
+    /// The largest icon suitable for a splash screen
+    pub splash_icon: Option<R::Url>,
+    pub orientation: Option<pwa::Orientation>,
+    pub display: Option<pwa::DisplayMode>,
+    pub shortcuts: R::Array<pwa::ShortcutItem<R>>,
+    pub default_url_patterns: Vec<String>,
+    pub manifest_url: Option<R::Url>,
+}
+
+impl<R: ResolveType> ScrapedSite<R> {
+    /// The name of the site, preferring the short site name over the title
+    pub fn name(&self) -> Option<&String> {
+        self.site_name.as_ref().or(self.title.as_ref())
+    }
+
+    /// The name of the site as `(lang, name, rtl)` for its language, if the language is known.
+    /// `rtl` is `None` if the site doesn't declare a text direction.
+    pub fn localized_name(&self) -> Option<(String, String, Option<bool>)> {
+        let rtl = match self.dir {
+            Some(pwa::TextDirection::LeftToRight) => Some(false),
+            Some(pwa::TextDirection::RightToLeft) => Some(true),
+            _ => None,
+        };
+        Some((self.lang.clone()?, self.name()?.clone(), rtl))
+    }
+}
+
+impl ScrapedSite<Resolved> {
+    pub fn supplemented(self, m: pwa::Manifest<Resolved>) -> ScrapedSite<Resolved> {
+        let display = m.effective_display();
+        let manifest_icons = m.icons.unwrap_or_default();
+
+        let splash_icon = manifest_icons
+            .iter()
+            .filter(|icon| icon.purpose.any)
+            .filter_map(|icon| {
+                let size = icon.sizes.sizes.iter().map(|&(w, h)| w.min(h)).max()?;
+                Some((size, icon))
+            })
+            .filter(|&(size, _)| size >= SPLASH_ICON_MIN_SIZE)
+            .max_by_key(|&(size, _)| size)
+            .map(|(_, icon)| icon.src.clone());
+
+        let mut icons = Vec::new();
+        let mut maskable_icons = Vec::new();
+        let mut monochrome_icons = Vec::new();
+        for icon in manifest_icons.into_iter().rev() {
+            if icon.purpose.maskable {
+                maskable_icons.push(icon.src.clone());
+            }
+            if icon.purpose.monochrome {
+                monochrome_icons.push(icon.src.clone());
+            }
+            // Monochrome icons are masks and look broken when used as regular icons
+            if icon.purpose.any || icon.purpose.maskable {
+                icons.push(icon.src);
+            }
+        }
+        icons.extend(self.icons.into_iter());
+        icons.prune_duplicates();
+
+        let mut url_patterns = self.default_url_patterns;
+
+        if let Some(scope_url) = m.scope {
+            let scope_url = scope_url.join("/").unwrap_or(scope_url);
+            if let Some(host_str) = scope_url.host_str() {
+                let scope_str = format!("https?://{}{}*", host_str, scope_url.path());
+                // Some sites' scope is actually insufficient (e.g. their scope is https://example.com/ but
+                // they redirect to https://m.example.com). Hence we don't replace the default patterns,
+                // but instead just add the scope pattern:
+                if !url_patterns.contains(&scope_str) {
+                    url_patterns.push(scope_str);
+                }
+            }
+        }
+
+        ScrapedSite {
+            site_name: m.short_name.or(self.site_name),
+            title: m.name.or(self.title),
+            lang: m.lang.map(|lang| lang.as_str().to_owned()).or(self.lang),
+            dir: m.dir.or(self.dir),
+            theme_color: m.theme_color.or(self.theme_color),
+            background_color: m.background_color.or(self.background_color),
+            icons,
+            maskable_icons,
+            monochrome_icons,
+            splash_icon: splash_icon.or(self.splash_icon),
+            orientation: m.orientation.or(self.orientation),
+            display: display.or(self.display),
+            shortcuts: m.shortcuts.unwrap_or_default(),
+            default_url_patterns: url_patterns,
+            manifest_url: self.manifest_url,
+        }
+    }
+}
+
+impl Fetchable for ScrapedSite<Unresolved> {
+    type Out<R: ResolveType> = ScrapedSite<R>;
+    type Error = String;
+
+    fn fetch(url: &Url) -> Result<ScrapedSite<Unresolved>, String> {
+        Self::fetch_localized(url, None)
+    }
+}
+
+impl ScrapedSite<Unresolved> {
+    /// Fetch the site at `url`, sending `language` as `Accept-Language` header
+    pub fn fetch_localized(
+        url: &Url,
+        language: Option<&str>,
+    ) -> Result<ScrapedSite<Unresolved>, String> {
+        let client = reqwest::blocking::ClientBuilder::new()
+            .cookie_store(true)
+            .build()
+            .map_err(|err| err.to_string())?;
+
+        let mut request = client.get(url.as_ref());
+        if let Some(language) = language {
+            request = request.header(reqwest::header::ACCEPT_LANGUAGE, language);
+        }
+        let body = request
+            .send()
+            .map_err(|err| err.to_string())?
+            .text()
+            .map_err(|err| err.to_string())?;
+
+        let html = scraper::Html::parse_document(&body);
+
+        let res = Self::parse(url, html);
+
+        Ok(res)
+    }
+
+    pub fn parse(url: &Url, html: scraper::Html) -> Self {
+        let title_sel = scraper::Selector::parse("html > head > title").unwrap();
+        let title = html
+            .select(&title_sel)
+            .next()
+            .map(|el| el.inner_html())
+            .map(|s| s.trim().to_owned());
+
+        let html_sel = scraper::Selector::parse("html").unwrap();
+        let root = html.select(&html_sel).next().map(|el| el.value());
+        let lang = root
+            .and_then(|el| el.attr("lang"))
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty());
+        let dir = root.and_then(|el| el.attr("dir")).and_then(|dir| {
+            match dir.trim().to_ascii_lowercase().as_str() {
+                "ltr" => Some(pwa::TextDirection::LeftToRight),
+                "rtl" => Some(pwa::TextDirection::RightToLeft),
+                "auto" => Some(pwa::TextDirection::Auto),
+                _ => None,
+            }
+        });
+
+        let og_name_sel =
+            scraper::Selector::parse("html > head > meta[property='og:site_name']").unwrap();
+        let site_name = html
+            .select(&og_name_sel)
+            .next()
+            .and_then(|el| el.value().attr("content").map(String::from))
+            .map(|s| s.trim().to_owned());
+
+        let theme_color_sel =
+            scraper::Selector::parse("html > head > meta[name='theme-color']").unwrap();
+        let theme_color = html
+            .select(&theme_color_sel)
+            .next()
+            .and_then(|el| el.value().attr("content"))
+            .and_then(|c| c.parse().ok());
+
+        let favicon_sel = scraper::Selector::parse("html > head > link[rel~='icon']").unwrap();
+        let shortcut_sel = scraper::Selector::parse("html > head > link[rel~='shortcut']").unwrap();
+        let apple_icon_sel =
+            scraper::Selector::parse("html > head > link[rel~='apple-touch-icon']").unwrap();
+        let opengraph_sel =
+            scraper::Selector::parse("html > head > meta[property~='og:image']").unwrap();
+
+        let favicon_url = html
+            .select(&favicon_sel)
+            .next()
+            .and_then(|el| el.value().attr("href").map(String::from));
+
+        let shortcut_icon_url = html
+            .select(&shortcut_sel)
+            .next()
+            .and_then(|el| el.value().attr("href").map(String::from));
+
+        let apple_icon_url = html
+            .select(&apple_icon_sel)
+            .next()
+            .and_then(|el| el.value().attr("href").map(String::from));
+
+        let open_graph_images = html
+            .select(&opengraph_sel)
+            .map(|el| el.value().attr("content").map(String::from));
+
+        let mut icons = vec![apple_icon_url];
+        icons.push(shortcut_icon_url);
+        icons.extend(open_graph_images);
+        icons.push(favicon_url);
+        icons.prune_duplicates();
+
+        let icons = LossyVec::from(icons);
+
+        let mut default_url_patterns = Vec::new();
+
+        // Create a default url pattern for any url with a host part
+        if let Some(host_str) = url.host_str() {
+            default_url_patterns.push(format!("https?://{}/*", host_str));
+        }
+
+        // Create a default url pattern for subdomains for any url whose host part is a domain
+        if let Some(url::Host::Domain(domain_str)) = url.host() {
+            default_url_patterns.push(format!("https?://*.{}/*", domain_str));
+        }
+
+        let manifest_sel = scraper::Selector::parse("html > head > link[rel='manifest']").unwrap();
+        let manifest_url = html
+            .select(&manifest_sel)
+            .next()
+            .and_then(|el| el.value().attr("href").map(String::from));
+
+        Self {
+            site_name,
+            title,
+            lang,
+            dir,
+            theme_color,
+            background_color: None,
+            icons,
+            maskable_icons: LossyVec::from(Vec::new()),
+            monochrome_icons: LossyVec::from(Vec::new()),
+            splash_icon: None,
+            orientation: None,
+            display: None,
+            shortcuts: LossyVec::from(Vec::new()),
+            default_url_patterns,
+            manifest_url,
+        }
+    }
+}
+
+impl Resolvable for ScrapedSite<Unresolved> {
+    type Out<R: ResolveType> = ScrapedSite<R>;
+
+    fn resolve(self, base_url: &Url) -> ScrapedSite<Resolved> {
+        ScrapedSite {
+            site_name: self.site_name,
+            title: self.title,
+            lang: self.lang,
+            dir: self.dir,
+            theme_color: self.theme_color,
+            background_color: self.background_color,
+            icons: Resolvable::resolve(self.icons, base_url)
+                .into_iter()
+                .flatten()
+                .collect(),
+            maskable_icons: Resolvable::resolve(self.maskable_icons, base_url)
+                .into_iter()
+                .flatten()
+                .collect(),
+            monochrome_icons: Resolvable::resolve(self.monochrome_icons, base_url)
+                .into_iter()
+                .flatten()
+                .collect(),
+            splash_icon: self.splash_icon.resolve(base_url).flatten(),
+            orientation: self.orientation,
+            display: self.display,
+            shortcuts: Resolvable::resolve(self.shortcuts, base_url)
+                .into_iter()
+                .flatten()
+                .collect(),
+            default_url_patterns: self.default_url_patterns,
+            manifest_url: self.manifest_url.resolve(base_url).flatten(),
+        }
+    }
+}
+
+/// Scrape the site at `url` and supplement the result with its web app manifest (if any)
+pub fn scrape(url: &Url) -> Result<ScrapedSite<Resolved>, String> {
+    scrape_localized(url, None)
+}
+
+/// Scrape the site like `scrape`, asking for the site and its manifest in `language`
+pub fn scrape_localized(
+    url: &Url,
+    language: Option<&str>,
+) -> Result<ScrapedSite<Resolved>, String> {
+    let res = ScrapedSite::fetch_localized(url, language)?.resolve(url);
+
+    let m = res
+        .manifest_url
+        .as_ref()
+        .map(|manifest_url| {
+            pwa::Manifest::fetch_localized(manifest_url, language).map(|m| m.resolve(manifest_url))
+        })
+        .and_then(Result::ok);
+
+    Ok(match m {
+        Some(m) => res.supplemented(m),
+        None => res,
+    })
+}
+
+/// Collect the names of `site` in its own language and in each of `languages` (language tags like
+/// `de` or `pt-BR`). Localized sites pick the language from the `Accept-Language` header, so the
+/// site at `url` is scraped again for each language. Names equal to the name of `site` are
+/// skipped, since most sites ignore the header. The names are returned like
+/// [`ScrapedSite::localized_name`], the language tags aren't validated.
+pub fn scrape_names(
+    url: &Url,
+    site: &ScrapedSite<Resolved>,
+    languages: &[String],
+) -> Vec<(String, String, Option<bool>)> {
+    let mut names = site.localized_name().into_iter().collect::<Vec<_>>();
+
+    for language in languages {
+        let localized = match scrape_localized(url, Some(language)) {
+            Ok(localized) => localized,
+            Err(_) => continue,
+        };
+        let name = match localized.name() {
+            Some(name) if Some(name) != site.name() => name,
+            _ => continue,
+        };
+        // Prefer the language the site declares, it may have fallen back to another language
+        let name = localized
+            .localized_name()
+            .unwrap_or_else(|| (language.clone(), name.clone(), None));
+        if !names.iter().any(|(lang, _, _)| *lang == name.0) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+pub fn validate_url(url: String) -> Result<Url, String> {
+    let url = Url::parse(&url).map_err(|err| err.to_string())?;
+    Ok(url)
+}
+
+trait PruneDuplicates {
+    fn prune_duplicates(&mut self);
+}
+
+impl<T: Eq + Hash + Clone> PruneDuplicates for Vec<T> {
+    fn prune_duplicates(&mut self) {
+        let mut seen = HashSet::new();
+        self.retain(|el| {
+            if seen.contains(el) {
+                false
+            } else {
+                seen.insert(el.clone());
+                true
+            }
+        });
+    }
+}